@@ -0,0 +1,141 @@
+use crate::api::openai::openai_resp_should_retry_without_script;
+use crate::clip_plan::ClipPlanList;
+use crate::planner::{self, Beat, BeatRequest, ClipPlanner, PlanRequest, PlannerError, PlannerReply};
+use crate::{logi, logw};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::json;
+use std::time::Duration;
+
+/// Planner for any OpenAI-compatible `/v1/chat/completions` server (llama.cpp, vLLM, a local mock, ...).
+pub struct ChatCompletionsPlanner {
+    client: Client,
+    url: String,
+    model: String,
+    api_key: String,
+}
+
+impl ChatCompletionsPlanner {
+    pub fn new(client: Client, base_url: &str, model: &str, api_key: &str) -> Self {
+        Self {
+            client,
+            url: format!("{}/chat/completions", base_url.trim_end_matches('/')),
+            model: model.to_string(),
+            api_key: api_key.to_string(),
+        }
+    }
+}
+
+fn chat_extract_message_content(resp_json: &str) -> Option<String> {
+    let root: serde_json::Value = serde_json::from_str(resp_json).ok()?;
+
+    if let Some(err) = root.get("error") {
+        if !err.is_null() {
+            if let Some(msg) = err.get("message").and_then(|v| v.as_str()) {
+                logw(format!("Chat completions error message: {}", msg));
+            }
+            return None;
+        }
+    }
+
+    let choice = root.get("choices")?.as_array()?.first()?;
+    let content = choice.get("message")?.get("content")?.as_str()?;
+    Some(strip_code_fence(content).to_string())
+}
+
+/// A response cut off at the token limit, reported like the Responses backend's `incomplete`
+/// status so the feedback loop asks again.
+fn chat_unusable_response(resp_json: &str) -> Option<PlannerError> {
+    let root: serde_json::Value = serde_json::from_str(resp_json).ok()?;
+    let choice = root.get("choices")?.as_array()?.first()?;
+    match choice.get("finish_reason").and_then(|v| v.as_str()) {
+        Some("length") => Some(PlannerError::Incomplete("finish_reason=length".to_string())),
+        _ => None,
+    }
+}
+
+/// Local models often wrap JSON in a Markdown fence even when asked not to.
+fn strip_code_fence(text: &str) -> &str {
+    let trimmed = text.trim();
+    let Some(rest) = trimmed.strip_prefix("```") else {
+        return trimmed;
+    };
+    let rest = rest.strip_prefix("json").unwrap_or(rest);
+    rest.strip_suffix("```").unwrap_or(rest).trim()
+}
+
+impl ChatCompletionsPlanner {
+    async fn send(&self, prompt: String, has_script: bool, timeout: Duration) -> Result<PlannerReply> {
+        let body = json!({
+            "model": self.model,
+            "messages": [
+                {"role": "system", "content": planner::PLAN_SYSTEM_PROMPT},
+                {"role": "user", "content": prompt},
+            ],
+            "response_format": {"type": "json_object"},
+        });
+
+        let mut request = self.client.post(&self.url).json(&body).timeout(timeout);
+        if !self.api_key.is_empty() {
+            request = request.bearer_auth(&self.api_key);
+        }
+        let resp = request.send().await.context("Chat completions request failed")?;
+
+        let status = resp.status();
+        let raw = resp.text().await.unwrap_or_default();
+
+        if !status.is_success() {
+            logw(format!("Chat completions HTTP {}", status.as_u16()));
+            if !raw.is_empty() {
+                let snippet = raw.chars().take(800).collect::<String>();
+                logw(format!("Chat completions raw body: {}", snippet));
+            }
+            let retry = has_script && openai_resp_should_retry_without_script(&raw);
+            return Ok(PlannerReply::Failed { retry_without_script: retry });
+        }
+
+        if let Some(err) = chat_unusable_response(&raw) {
+            return Err(err.into());
+        }
+
+        let Some(out_text) = chat_extract_message_content(&raw) else {
+            logw("Chat completions response parse failed.");
+            if !raw.is_empty() {
+                let snippet = raw.chars().take(800).collect::<String>();
+                logw(format!("Chat completions raw body: {}", snippet));
+            }
+            let retry = has_script && openai_resp_should_retry_without_script(&raw);
+            return Ok(PlannerReply::Failed { retry_without_script: retry });
+        };
+        Ok(PlannerReply::Text(out_text))
+    }
+}
+
+#[async_trait]
+impl ClipPlanner for ChatCompletionsPlanner {
+    fn describe(&self) -> String {
+        format!("chat completions ({} @ {})", self.model, self.url)
+    }
+
+    async fn make_plan(&self, req: &PlanRequest<'_>) -> Result<(ClipPlanList, bool)> {
+        let prompt = planner::build_plan_prompt(req);
+        let has_script = !req.script_text.is_empty();
+        match self.send(prompt, has_script, planner::plan_timeout(req)).await? {
+            PlannerReply::Text(text) => {
+                let plan = planner::parse_plan_output(&text)?;
+                logi(format!("Chat completions plan received: {} clips", plan.items.len()));
+                Ok((plan, false))
+            }
+            PlannerReply::Failed { retry_without_script } => Ok((ClipPlanList::default(), retry_without_script)),
+        }
+    }
+
+    async fn summarize_window(&self, req: &BeatRequest<'_>) -> Result<Vec<Beat>> {
+        let prompt = planner::build_beat_prompt(req);
+        match self.send(prompt, false, planner::BEAT_TIMEOUT).await? {
+            PlannerReply::Text(text) => Ok(planner::parse_beats_output(&text)?),
+            PlannerReply::Failed { .. } => Ok(Vec::new()),
+        }
+    }
+}
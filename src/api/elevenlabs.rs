@@ -1,8 +1,14 @@
-use crate::config::Config;
-use crate::{logw};
+use crate::captions::{self, WordTiming};
+use crate::config::{self, Config, LanguageProfile};
+use crate::narration::NarrationSynth;
+use crate::{logi, logw};
 use anyhow::{Context, Result};
+use async_trait::async_trait;
+use base64::Engine;
 use reqwest::Client;
+use serde::Deserialize;
 use std::path::Path;
+use std::sync::Arc;
 use tokio::fs;
 
 pub async fn elevenlabs_tts_to_mp3(
@@ -46,3 +52,149 @@ pub async fn elevenlabs_tts_to_mp3(
 
     Ok(fs::metadata(out_mp3_path).await.is_ok())
 }
+
+/// Models that only speak English; other languages need a multilingual model.
+const ENGLISH_ONLY_MODELS: &[&str] = &["eleven_monolingual_v1", "eleven_english_sts_v2", "eleven_turbo_v2", "eleven_flash_v2"];
+
+const MULTILINGUAL_MODEL: &str = "eleven_multilingual_v2";
+
+#[derive(Debug, Deserialize)]
+struct CharacterAlignment {
+    characters: Vec<String>,
+    character_start_times_seconds: Vec<f64>,
+    character_end_times_seconds: Vec<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TimestampedSpeech {
+    audio_base64: String,
+    #[serde(default)]
+    alignment: Option<CharacterAlignment>,
+}
+
+/// The `/with-timestamps` variant of [`elevenlabs_tts_to_mp3`]: same audio, plus per-character
+/// timings grouped into words. `Ok(None)` is a failed request; an answer that cannot be decoded
+/// falls back to the plain endpoint with no timings.
+pub async fn elevenlabs_tts_with_timestamps(
+    client: &Client,
+    cfg: &Config,
+    text: &str,
+    out_mp3_path: &Path,
+) -> Result<Option<Vec<WordTiming>>> {
+    let url = format!(
+        "https://api.elevenlabs.io/v1/text-to-speech/{}/with-timestamps?output_format=mp3_44100_128",
+        cfg.eleven_voice_id
+    );
+
+    let body = serde_json::json!({
+        "text": text,
+        "model_id": cfg.eleven_model_id,
+    });
+
+    let resp = client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .header("xi-api-key", &cfg.elevenlabs_key)
+        .json(&body)
+        .timeout(std::time::Duration::from_secs(300))
+        .send()
+        .await
+        .context("ElevenLabs request failed")?;
+
+    if !resp.status().is_success() {
+        logw(format!("ElevenLabs TTS (with timestamps) failed HTTP {}", resp.status().as_u16()));
+        return Ok(None);
+    }
+
+    let text_body = resp.text().await.context("ElevenLabs response read failed")?;
+    let decoded = serde_json::from_str::<TimestampedSpeech>(&text_body)
+        .map_err(|err| format!("response unreadable ({})", err))
+        .and_then(|speech| {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(speech.audio_base64.as_bytes())
+                .map_err(|err| format!("audio_base64 is not valid base64 ({})", err))?;
+            Ok((speech, bytes))
+        });
+    let (speech, bytes) = match decoded {
+        Ok(found) => found,
+        Err(reason) => {
+            logw(format!("ElevenLabs TTS with timestamps: {}; retrying without word timings.", reason));
+            let ok = elevenlabs_tts_to_mp3(client, cfg, text, out_mp3_path).await?;
+            return Ok(ok.then(Vec::new));
+        }
+    };
+    if let Some(parent) = out_mp3_path.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .with_context(|| format!("Failed to create dir {}", parent.display()))?;
+    }
+    fs::write(out_mp3_path, &bytes).await?;
+    if fs::metadata(out_mp3_path).await.is_err() {
+        return Ok(None);
+    }
+
+    let words = match speech.alignment {
+        Some(a) => captions::words_from_characters(&a.characters, &a.character_start_times_seconds, &a.character_end_times_seconds),
+        None => Vec::new(),
+    };
+    Ok(Some(words))
+}
+
+pub struct ElevenLabsSynth {
+    client: Client,
+    cfg: Config,
+}
+
+impl ElevenLabsSynth {
+    pub fn new(client: Client, cfg: &Config) -> Self {
+        Self {
+            client,
+            cfg: cfg.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl NarrationSynth for ElevenLabsSynth {
+    fn describe(&self) -> String {
+        format!("ElevenLabs ({} / {})", self.cfg.eleven_voice_id, self.cfg.eleven_model_id)
+    }
+
+    fn cache_key(&self) -> String {
+        format!("elevenlabs:{}:{}", self.cfg.eleven_voice_id, self.cfg.eleven_model_id)
+    }
+
+    async fn synthesize(&self, text: &str, out_mp3_path: &Path) -> Result<bool> {
+        elevenlabs_tts_to_mp3(&self.client, &self.cfg, text, out_mp3_path).await
+    }
+
+    async fn synthesize_timed(&self, text: &str, out_mp3_path: &Path) -> Result<Option<Vec<WordTiming>>> {
+        elevenlabs_tts_with_timestamps(&self.client, &self.cfg, text, out_mp3_path).await
+    }
+
+    fn with_voice(&self, voice_id: &str) -> Option<Arc<dyn NarrationSynth>> {
+        let mut cfg = self.cfg.clone();
+        cfg.eleven_voice_id = voice_id.to_string();
+        Some(Arc::new(Self {
+            client: self.client.clone(),
+            cfg,
+        }))
+    }
+
+    fn with_language(&self, code: &str, profile: &LanguageProfile) -> Option<Arc<dyn NarrationSynth>> {
+        let mut cfg = self.cfg.clone();
+        if !profile.voice_id.is_empty() {
+            cfg.eleven_voice_id = profile.voice_id.clone();
+        }
+        if !profile.model_id.is_empty() {
+            cfg.eleven_model_id = profile.model_id.clone();
+        } else if !config::is_english(code) && ENGLISH_ONLY_MODELS.contains(&cfg.eleven_model_id.as_str()) {
+            logi(format!("{} is English-only; using {} for {}", cfg.eleven_model_id, MULTILINGUAL_MODEL, code));
+            cfg.eleven_model_id = MULTILINGUAL_MODEL.to_string();
+        }
+        Some(Arc::new(Self {
+            client: self.client.clone(),
+            cfg,
+        }))
+    }
+}
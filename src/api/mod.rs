@@ -1,2 +1,3 @@
+pub mod chat_completions;
 pub mod elevenlabs;
 pub mod openai;
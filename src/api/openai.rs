@@ -1,28 +1,11 @@
 use crate::clip_plan::ClipPlanList;
-use crate::config::Config;
+use crate::planner::{self, Beat, BeatRequest, ClipPlanner, PlanRequest, PlannerError, PlannerReply};
 use crate::{logi, logw};
 use anyhow::{Context, Result};
+use async_trait::async_trait;
 use reqwest::Client;
 use serde_json::json;
-
-const MAX_SUB_CHARS: usize = 320_000;
-const MAX_SCRIPT_CHARS: usize = 80_000;
-
-fn sanitize_utf8_lossy(input: &str) -> String {
-    String::from_utf8_lossy(input.as_bytes()).into_owned()
-}
-
-fn trim_copy_utf8_safe(input: &str, max_bytes: usize) -> String {
-    if input.len() <= max_bytes {
-        return input.to_string();
-    }
-
-    let mut cut = max_bytes.min(input.len());
-    while cut > 0 && !input.is_char_boundary(cut) {
-        cut -= 1;
-    }
-    input[..cut].to_string()
-}
+use std::time::Duration;
 
 fn openai_extract_output_text(resp_json: &str) -> Option<String> {
     let root: serde_json::Value = serde_json::from_str(resp_json).ok()?;
@@ -91,7 +74,37 @@ fn openai_extract_output_text(resp_json: &str) -> Option<String> {
     None
 }
 
-fn openai_resp_should_retry_without_script(resp_json: &str) -> bool {
+/// A refusal or a truncated response, which `openai_extract_output_text` cannot tell apart
+/// from a missing answer.
+fn openai_unusable_response(resp_json: &str) -> Option<PlannerError> {
+    let root: serde_json::Value = serde_json::from_str(resp_json).ok()?;
+
+    let refusal = root
+        .get("output")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|item| item.get("content").and_then(|v| v.as_array()))
+        .flatten()
+        .find(|entry| entry.get("type").and_then(|v| v.as_str()) == Some("refusal"));
+    if let Some(entry) = refusal {
+        let text = entry.get("refusal").and_then(|v| v.as_str()).unwrap_or("no reason given");
+        return Some(PlannerError::Refused(text.to_string()));
+    }
+
+    if root.get("status").and_then(|v| v.as_str()) == Some("incomplete") {
+        let reason = root
+            .get("incomplete_details")
+            .and_then(|d| d.get("reason"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown reason");
+        return Some(PlannerError::Incomplete(reason.to_string()));
+    }
+
+    None
+}
+
+pub(crate) fn openai_resp_should_retry_without_script(resp_json: &str) -> bool {
     if resp_json.is_empty() {
         return false;
     }
@@ -143,74 +156,113 @@ fn openai_resp_should_retry_without_script(resp_json: &str) -> bool {
     yes
 }
 
-pub async fn openai_make_plan(
-    client: &Client,
-    cfg: &Config,
-    movie_title: &str,
-    subs_seconds_text: &str,
-    optional_script_text: &str,
-    num_clips: i32,
-) -> Result<(ClipPlanList, bool)> {
-    let title_utf8 = sanitize_utf8_lossy(movie_title);
-    let subs_utf8 = sanitize_utf8_lossy(subs_seconds_text);
-    let script_utf8 = sanitize_utf8_lossy(optional_script_text);
-
-    let subs_trim = trim_copy_utf8_safe(&subs_utf8, MAX_SUB_CHARS);
-    let script_trim = trim_copy_utf8_safe(&script_utf8, MAX_SCRIPT_CHARS);
-
-    let prompt = format!(
-        "You are given TWO inputs.\nMovie: {}\n\nINPUT A (Subtitles with timestamps in SECONDS):\n{}\n\nINPUT B (Optional script text WITHOUT timestamps; may be empty):\n{}\n\nTASK:\n- Choose {} non-overlapping time ranges that best cover the full plot arc.\n- ONLY use INPUT A for selecting start/end times (seconds). INPUT B is for story context.\n- Each time range should usually be 8-16 seconds long (end-start). Avoid >20 seconds.\n- Keep narrations punchy but not tiny: about 20-35 words total, in 3-5 short sentences.\n- Prefer ranges with clear visual action (reveals, confrontations, entrances, big moments).\n- Skip any range that starts at 0.\n- Return STRICT JSON with this shape ONLY:\n  {{\"clips\":[{{\"start\":120,\"end\":145,\"narration\":\"...\"}}, ...]}}\n- Clips must be increasing by start time.\n- Each narration must be at least 3 full sentences, casual commentator vibe.\n- The first narration must start with: \"Here we go, let's go over the movie {}.\".\n",
-        title_utf8, subs_trim, script_trim, num_clips, title_utf8
-    );
-
-    let body = json!({
-        "model": "gpt-5.2",
-        "reasoning": {"effort": "high"},
-        "input": [
-            {"role": "system", "content": "You are a helpful assistant designed to output JSON."},
-            {"role": "user", "content": prompt},
-        ],
-        "text": {"format": {"type": "json_object"}},
-    });
-
-    let has_script = !optional_script_text.is_empty();
-    let timeout_s = if has_script { 14_400 } else { 3_600 };
-
-    let resp = client
-        .post("https://api.openai.com/v1/responses")
-        .bearer_auth(&cfg.openai_key)
-        .json(&body)
-        .timeout(std::time::Duration::from_secs(timeout_s))
-        .send()
-        .await
-        .context("OpenAI request failed")?;
-
-    let status = resp.status();
-    let raw = resp.text().await.unwrap_or_default();
-
-    if !status.is_success() {
-        logw(format!("OpenAI HTTP {}", status.as_u16()));
-        if !raw.is_empty() {
-            let snippet = raw.chars().take(800).collect::<String>();
-            logw(format!("OpenAI raw body: {}", snippet));
+/// Planner backed by the OpenAI Responses API (`{base_url}/responses`).
+pub struct OpenAiResponsesPlanner {
+    client: Client,
+    url: String,
+    model: String,
+    api_key: String,
+}
+
+impl OpenAiResponsesPlanner {
+    pub fn new(client: Client, base_url: &str, model: &str, api_key: &str) -> Self {
+        Self {
+            client,
+            url: format!("{}/responses", base_url.trim_end_matches('/')),
+            model: model.to_string(),
+            api_key: api_key.to_string(),
         }
+    }
+}
+
+impl OpenAiResponsesPlanner {
+    async fn send(&self, prompt: String, schema_name: &str, schema: serde_json::Value, has_script: bool, timeout: Duration) -> Result<PlannerReply> {
+        let body = json!({
+            "model": self.model,
+            "reasoning": {"effort": "high"},
+            "input": [
+                {"role": "system", "content": planner::PLAN_SYSTEM_PROMPT},
+                {"role": "user", "content": prompt},
+            ],
+            "text": {
+                "format": {
+                    "type": "json_schema",
+                    "name": schema_name,
+                    "strict": true,
+                    "schema": schema,
+                }
+            },
+        });
+
+        let resp = self
+            .client
+            .post(&self.url)
+            .bearer_auth(&self.api_key)
+            .json(&body)
+            .timeout(timeout)
+            .send()
+            .await
+            .context("OpenAI request failed")?;
+
+        let status = resp.status();
+        let raw = resp.text().await.unwrap_or_default();
 
-        let retry = has_script && openai_resp_should_retry_without_script(&raw);
-        return Ok((ClipPlanList::default(), retry));
+        if !status.is_success() {
+            logw(format!("OpenAI HTTP {}", status.as_u16()));
+            if !raw.is_empty() {
+                let snippet = raw.chars().take(800).collect::<String>();
+                logw(format!("OpenAI raw body: {}", snippet));
+            }
+
+            let retry = has_script && openai_resp_should_retry_without_script(&raw);
+            return Ok(PlannerReply::Failed { retry_without_script: retry });
+        }
+
+        if let Some(err) = openai_unusable_response(&raw) {
+            return Err(err.into());
+        }
+
+        let Some(out_text) = openai_extract_output_text(&raw) else {
+            logw("OpenAI response parse failed.");
+            if !raw.is_empty() {
+                let snippet = raw.chars().take(800).collect::<String>();
+                logw(format!("OpenAI raw body: {}", snippet));
+            }
+            let retry = has_script && openai_resp_should_retry_without_script(&raw);
+            return Ok(PlannerReply::Failed { retry_without_script: retry });
+        };
+        Ok(PlannerReply::Text(out_text))
+    }
+}
+
+#[async_trait]
+impl ClipPlanner for OpenAiResponsesPlanner {
+    fn describe(&self) -> String {
+        format!("OpenAI Responses ({})", self.model)
     }
 
-    let out_text = openai_extract_output_text(&raw);
-    if out_text.is_none() {
-        logw("OpenAI response parse failed.".to_string());
-        if !raw.is_empty() {
-            let snippet = raw.chars().take(800).collect::<String>();
-            logw(format!("OpenAI raw body: {}", snippet));
+    async fn make_plan(&self, req: &PlanRequest<'_>) -> Result<(ClipPlanList, bool)> {
+        let prompt = planner::build_plan_prompt(req);
+        let has_script = !req.script_text.is_empty();
+        let reply = self
+            .send(prompt, "clip_plan", ClipPlanList::json_schema(), has_script, planner::plan_timeout(req))
+            .await?;
+
+        match reply {
+            PlannerReply::Text(text) => {
+                let plan = planner::parse_plan_output(&text)?;
+                logi(format!("OpenAI plan received: {} clips", plan.items.len()));
+                Ok((plan, false))
+            }
+            PlannerReply::Failed { retry_without_script } => Ok((ClipPlanList::default(), retry_without_script)),
         }
-        let retry = has_script && openai_resp_should_retry_without_script(&raw);
-        return Ok((ClipPlanList::default(), retry));
     }
 
-    let plan = ClipPlanList::from_json(&out_text.unwrap())?;
-    logi(format!("OpenAI plan received: {} clips", plan.items.len()));
-    Ok((plan, false))
+    async fn summarize_window(&self, req: &BeatRequest<'_>) -> Result<Vec<Beat>> {
+        let prompt = planner::build_beat_prompt(req);
+        match self.send(prompt, "beats", planner::beat_schema(), false, planner::BEAT_TIMEOUT).await? {
+            PlannerReply::Text(text) => Ok(planner::parse_beats_output(&text)?),
+            PlannerReply::Failed { .. } => Ok(Vec::new()),
+        }
+    }
 }
@@ -0,0 +1,439 @@
+use crate::config::{CaptionConfig, CaptionPosition};
+use crate::logw;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::fs;
+
+/// Canvas the ASS coordinates refer to; libass scales it to the real (9:16) output size.
+const PLAY_RES_X: u32 = 1080;
+const PLAY_RES_Y: u32 = 1920;
+
+/// How long a caption line stays up after its last word, unless the next line starts sooner.
+const LINE_LINGER_SECS: f64 = 0.3;
+
+/// A pause this long starts a new caption line even if the current one has room.
+const LINE_BREAK_PAUSE_SECS: f64 = 0.7;
+
+/// One spoken word and when it is heard, in seconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WordTiming {
+    pub word: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// Groups per-character timings (as returned by ElevenLabs) into words.
+pub fn words_from_characters(characters: &[String], starts: &[f64], ends: &[f64]) -> Vec<WordTiming> {
+    let mut words = Vec::new();
+    let mut current: Option<WordTiming> = None;
+    for ((ch, &start), &end) in characters.iter().zip(starts).zip(ends) {
+        if ch.trim().is_empty() {
+            words.extend(current.take());
+            continue;
+        }
+        match current.as_mut() {
+            Some(word) => {
+                word.word.push_str(ch);
+                word.end = end;
+            }
+            None => {
+                current = Some(WordTiming {
+                    word: ch.clone(),
+                    start,
+                    end,
+                })
+            }
+        }
+    }
+    words.extend(current);
+    words
+}
+
+/// Fallback when the narration backend reports no timings: spreads the words over the
+/// narration in proportion to their length.
+pub fn estimate_word_timings(text: &str, duration: f64) -> Vec<WordTiming> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.is_empty() || duration <= 0.0 {
+        return Vec::new();
+    }
+    // +1 per word stands in for the gap after it; the last word runs to the end.
+    let weight_total: usize = words.iter().map(|w| w.chars().count() + 1).sum::<usize>() - 1;
+
+    let per_weight = duration / weight_total as f64;
+    let mut at = 0.0;
+    words
+        .into_iter()
+        .map(|word| {
+            let len = word.chars().count();
+            let timing = WordTiming {
+                word: word.to_string(),
+                start: at,
+                end: at + len as f64 * per_weight,
+            };
+            at += (len + 1) as f64 * per_weight;
+            timing
+        })
+        .collect()
+}
+
+/// Moves every word by `offset` seconds and drops anything past `duration` (clip length).
+pub fn shift_words(words: &[WordTiming], offset: f64, duration: f64) -> Vec<WordTiming> {
+    words
+        .iter()
+        .filter(|w| w.start < duration)
+        .map(|w| WordTiming {
+            word: w.word.clone(),
+            start: offset + w.start,
+            end: offset + w.end.min(duration),
+        })
+        .collect()
+}
+
+pub async fn save_words<T: Serialize + ?Sized>(path: &Path, words: &T) -> Result<()> {
+    let json = serde_json::to_string(words)?;
+    fs::write(path, json)
+        .await
+        .with_context(|| format!("Failed to write word timings: {}", path.display()))
+}
+
+pub async fn load_words<T: for<'de> Deserialize<'de>>(path: &Path) -> Option<T> {
+    let text = fs::read_to_string(path).await.ok()?;
+    match serde_json::from_str(&text) {
+        Ok(words) => Some(words),
+        Err(err) => {
+            logw(format!("Word timings unreadable ({}): {}", err, path.display()));
+            None
+        }
+    }
+}
+
+/// `#RRGGBB` -> ASS `BBGGRR` (styles add `&H00`, inline `\c` tags wrap it in `&H..&`).
+fn ass_color(hex: &str, fallback: &str) -> String {
+    let digits = hex.trim().trim_start_matches('#');
+    let valid = digits.len() == 6 && digits.chars().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        logw(format!("Caption colour {:?} is not #RRGGBB; using {}", hex, fallback));
+        return ass_color(fallback, "#FFFFFF");
+    }
+    let (r, g, b) = (&digits[0..2], &digits[2..4], &digits[4..6]);
+    format!("{}{}{}", b, g, r).to_uppercase()
+}
+
+/// `H:MM:SS.cc`
+fn ass_time(secs: f64) -> String {
+    let cs = (secs.max(0.0) * 100.0).round() as u64;
+    format!("{}:{:02}:{:02}.{:02}", cs / 360_000, (cs / 6000) % 60, (cs / 100) % 60, cs % 100)
+}
+
+/// Braces start override blocks and backslashes start tags, so neither may reach libass raw.
+fn ass_text(word: &str) -> String {
+    word.replace('{', "(").replace('}', ")").replace('\\', "/")
+}
+
+/// Splits one clip's words into caption lines of at most `per_line` words, breaking early at pauses.
+fn caption_lines(words: &[WordTiming], per_line: usize) -> Vec<&[WordTiming]> {
+    let per_line = per_line.max(1);
+    let mut lines = Vec::new();
+    let mut begin = 0;
+    for i in 1..=words.len() {
+        let full = i - begin >= per_line;
+        let pause = i < words.len() && words[i].start - words[i - 1].end >= LINE_BREAK_PAUSE_SECS;
+        if i == words.len() || full || pause {
+            lines.push(&words[begin..i]);
+            begin = i;
+        }
+    }
+    lines
+}
+
+/// Builds an ASS script from per-clip word timings (already on the output timeline): each
+/// line shows a few words, and the word being spoken is drawn in the highlight colour.
+pub fn build_ass(timeline: &[Vec<WordTiming>], cfg: &CaptionConfig) -> String {
+    let alignment = match cfg.position {
+        CaptionPosition::Bottom => 2,
+        CaptionPosition::Middle => 5,
+        CaptionPosition::Top => 8,
+    };
+    let highlight = ass_color(&cfg.highlight_color, "#FFD400");
+
+    let mut out = String::new();
+    out.push_str("[Script Info]\nScriptType: v4.00+\n");
+    out.push_str(&format!("PlayResX: {}\nPlayResY: {}\n", PLAY_RES_X, PLAY_RES_Y));
+    out.push_str("WrapStyle: 0\nScaledBorderAndShadow: yes\n\n");
+    out.push_str("[V4+ Styles]\n");
+    out.push_str("Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding\n");
+    out.push_str(&format!(
+        "Style: Caption,{},{},&H00{},&H00{},&H00{},&H80000000,0,0,0,0,100,100,0,0,1,{},0,{},60,60,{},1\n\n",
+        cfg.font_name,
+        cfg.font_size,
+        ass_color(&cfg.color, "#FFFFFF"),
+        highlight,
+        ass_color(&cfg.outline_color, "#000000"),
+        cfg.outline,
+        alignment,
+        cfg.margin
+    ));
+    out.push_str("[Events]\nFormat: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n");
+
+    let lines: Vec<&[WordTiming]> = timeline
+        .iter()
+        .flat_map(|clip| caption_lines(clip, cfg.words_per_line))
+        .collect();
+    for (index, line) in lines.iter().enumerate() {
+        let Some(last) = line.last() else {
+            continue;
+        };
+        let mut line_end = last.end + LINE_LINGER_SECS;
+        if let Some(next) = lines.get(index + 1).and_then(|l| l.first()) {
+            line_end = line_end.min(next.start);
+        }
+
+        for (k, word) in line.iter().enumerate() {
+            let start = word.start;
+            let end = line.get(k + 1).map(|w| w.start).unwrap_or(line_end);
+            if end - start < 0.01 {
+                continue;
+            }
+            let text: Vec<String> = line
+                .iter()
+                .enumerate()
+                .map(|(j, w)| {
+                    if j == k {
+                        format!("{{\\c&H{}&}}{}{{\\r}}", highlight, ass_text(&w.word))
+                    } else {
+                        ass_text(&w.word)
+                    }
+                })
+                .collect();
+            out.push_str(&format!(
+                "Dialogue: 0,{},{},Caption,,0,0,0,,{}\n",
+                ass_time(start),
+                ass_time(end),
+                text.join(" ")
+            ));
+        }
+    }
+    out
+}
+
+/// One subtitle cue of the narration sidecar files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+}
+
+/// Splits the narration into cues, one sentence each where it fits in `max_chars`. Cues never
+/// span two clips, since each clip's narration starts fresh on the timeline.
+pub fn narration_cues(timeline: &[Vec<WordTiming>], max_chars: usize) -> Vec<Cue> {
+    let max_chars = max_chars.max(16);
+    let mut cues = Vec::new();
+    for clip in timeline {
+        let mut current: Vec<&WordTiming> = Vec::new();
+        let mut chars = 0;
+        for word in clip {
+            let len = word.word.chars().count();
+            if !current.is_empty() && chars + 1 + len > max_chars {
+                cues.push(cue_from(&current));
+                current.clear();
+                chars = 0;
+            }
+            chars += if current.is_empty() { len } else { len + 1 };
+            current.push(word);
+            if word.word.ends_with(['.', '!', '?', '…']) {
+                cues.push(cue_from(&current));
+                current.clear();
+                chars = 0;
+            }
+        }
+        if !current.is_empty() {
+            cues.push(cue_from(&current));
+        }
+    }
+
+    // Hold each cue a little past its last word, but never into the next one.
+    for i in 0..cues.len() {
+        let limit = cues.get(i + 1).map(|next| next.start).unwrap_or(f64::MAX);
+        cues[i].end = (cues[i].end + LINE_LINGER_SECS).min(limit);
+    }
+    for cue in &mut cues {
+        cue.text = wrap_cue(&cue.text, max_chars / 2);
+    }
+    cues
+}
+
+fn cue_from(words: &[&WordTiming]) -> Cue {
+    Cue {
+        start: words.first().map(|w| w.start).unwrap_or(0.0),
+        end: words.last().map(|w| w.end).unwrap_or(0.0),
+        text: words.iter().map(|w| w.word.as_str()).collect::<Vec<_>>().join(" "),
+    }
+}
+
+/// Breaks text longer than `line_chars` onto two lines at the space nearest the middle.
+fn wrap_cue(text: &str, line_chars: usize) -> String {
+    if text.chars().count() <= line_chars {
+        return text.to_string();
+    }
+    let middle = text.len() / 2;
+    let split = text
+        .match_indices(' ')
+        .map(|(i, _)| i)
+        .min_by_key(|i| i.abs_diff(middle));
+    match split {
+        Some(i) => format!("{}\n{}", &text[..i], &text[i + 1..]),
+        None => text.to_string(),
+    }
+}
+
+/// `HH:MM:SS{sep}mmm`
+fn cue_time(secs: f64, sep: char) -> String {
+    let ms = (secs.max(0.0) * 1000.0).round() as u64;
+    format!("{:02}:{:02}:{:02}{}{:03}", ms / 3_600_000, (ms / 60_000) % 60, (ms / 1000) % 60, sep, ms % 1000)
+}
+
+pub fn to_srt(cues: &[Cue]) -> String {
+    let mut out = String::new();
+    for (index, cue) in cues.iter().enumerate() {
+        out.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            index + 1,
+            cue_time(cue.start, ','),
+            cue_time(cue.end, ','),
+            cue.text
+        ));
+    }
+    out
+}
+
+pub fn to_vtt(cues: &[Cue]) -> String {
+    let mut out = String::from("WEBVTT\n\n");
+    for cue in cues {
+        out.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            cue_time(cue.start, '.'),
+            cue_time(cue.end, '.'),
+            cue.text
+        ));
+    }
+    out
+}
+
+/// Three-letter code the mp4 muxer expects for track languages, for the languages with
+/// built-in profiles; `None` leaves the track untagged.
+pub fn iso639_2(code: &str) -> Option<&'static str> {
+    let primary = code.split(['-', '_']).next().unwrap_or(code).to_ascii_lowercase();
+    match primary.as_str() {
+        "" | "en" => Some("eng"),
+        "de" => Some("ger"),
+        "es" => Some("spa"),
+        "fr" => Some("fre"),
+        "it" => Some("ita"),
+        "pt" => Some("por"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(text: &str, start: f64, end: f64) -> WordTiming {
+        WordTiming {
+            word: text.to_string(),
+            start,
+            end,
+        }
+    }
+
+    #[test]
+    fn ass_time_rounds_to_centiseconds() {
+        assert_eq!(ass_time(0.0), "0:00:00.00");
+        assert_eq!(ass_time(-1.0), "0:00:00.00");
+        assert_eq!(ass_time(3725.456), "1:02:05.46");
+        assert_eq!(ass_time(59.996), "0:01:00.00");
+    }
+
+    #[test]
+    fn caption_lines_break_at_pauses_and_width() {
+        let words = vec![
+            word("one", 0.0, 0.3),
+            word("two", 0.4, 0.7),
+            word("three", 0.8, 1.1),
+            word("four", 1.2, 1.5),
+            word("five", 2.5, 2.8),
+        ];
+        let lines: Vec<Vec<&str>> = caption_lines(&words, 3)
+            .iter()
+            .map(|line| line.iter().map(|w| w.word.as_str()).collect())
+            .collect();
+        assert_eq!(lines, vec![vec!["one", "two", "three"], vec!["four"], vec!["five"]]);
+        assert!(caption_lines(&[], 3).is_empty());
+    }
+
+    #[test]
+    fn estimated_timings_fill_the_duration() {
+        let words = estimate_word_timings("a bb  ccc", 7.0);
+        let texts: Vec<&str> = words.iter().map(|w| w.word.as_str()).collect();
+        assert_eq!(texts, vec!["a", "bb", "ccc"]);
+        assert_eq!(words[0].start, 0.0);
+        assert!((words[2].end - 7.0).abs() < 1e-9);
+        assert!(words.windows(2).all(|w| w[0].end < w[1].start));
+        assert!(estimate_word_timings("", 5.0).is_empty());
+        assert!(estimate_word_timings("word", 0.0).is_empty());
+    }
+
+    #[test]
+    fn cue_time_rounds_to_milliseconds() {
+        assert_eq!(cue_time(61.2346, ','), "00:01:01,235");
+        assert_eq!(cue_time(3599.9996, '.'), "01:00:00.000");
+        assert_eq!(cue_time(-0.5, ','), "00:00:00,000");
+    }
+
+    #[test]
+    fn narration_cues_split_at_sentences_and_clips() {
+        let timeline = vec![
+            vec![
+                word("Hello", 0.0, 0.4),
+                word("there.", 0.5, 0.9),
+                word("We", 1.0, 1.1),
+                word("meet", 1.2, 1.5),
+                word("again", 1.6, 2.0),
+            ],
+            vec![word("Later", 2.2, 2.6)],
+        ];
+        let cues = narration_cues(&timeline, 16);
+        let spans: Vec<(f64, f64, &str)> = cues.iter().map(|c| (c.start, c.end, c.text.as_str())).collect();
+        assert_eq!(
+            spans,
+            vec![(0.0, 1.0, "Hello\nthere."), (1.0, 2.2, "We meet\nagain"), (2.2, 2.9, "Later")]
+        );
+    }
+
+    #[test]
+    fn narration_cues_split_long_runs() {
+        let timeline = vec![vec![word("aaaaaaaa", 0.0, 1.0), word("bbbbbbbb", 1.0, 2.0)]];
+        let texts: Vec<String> = narration_cues(&timeline, 16).into_iter().map(|c| c.text).collect();
+        assert_eq!(texts, vec!["aaaaaaaa", "bbbbbbbb"]);
+    }
+
+    #[test]
+    fn wrap_cue_breaks_near_the_middle() {
+        assert_eq!(wrap_cue("short", 10), "short");
+        assert_eq!(wrap_cue("one two three four", 8), "one two\nthree four");
+        assert_eq!(wrap_cue("averyveryverylongword", 5), "averyveryverylongword");
+    }
+
+    #[test]
+    fn srt_numbers_cues_from_one() {
+        let cues = vec![Cue {
+            start: 1.5,
+            end: 3.0,
+            text: "Hi".to_string(),
+        }];
+        assert_eq!(to_srt(&cues), "1\n00:00:01,500 --> 00:00:03,000\nHi\n\n");
+        assert_eq!(to_vtt(&cues), "WEBVTT\n\n00:00:01.500 --> 00:00:03.000\nHi\n\n");
+    }
+}
@@ -1,18 +1,228 @@
-use anyhow::Result;
-use ai_movie_shorts::generator::run_generation;
+use anyhow::{Context, Result};
+use ai_movie_shorts::generator::{self, GenerationOptions};
 use ai_movie_shorts::init;
+use std::path::{Path, PathBuf};
+
+const USAGE: &str = "\
+Usage: ai-movie-cli [global options] [command]
+
+Commands:
+  run [--plan-only]                Process every movie in movies/ (default); --plan-only
+                                   stops after planning and writes plans/{title}.plan.json
+  plan <movie> [--out plan.json]   Fetch subtitles and write the clip plan for review
+                                   (default: plans/{title}.plan.json)
+  render [<movie>] --plan plan.json
+                                   Render from an existing (e.g. hand-edited) clip plan;
+                                   <movie> defaults to the one recorded in the plan
+  fetch-subs <movie>               Run only the subtitle source chain
+  tts <text> [--out out.mp3]       Voice a line with the configured narration backend
+  vertical <in> <out>              Convert a horizontal video to the vertical format
+                                   (layout and reframing from config vertical)
+
+<movie> is a path to a movie file or a title found in movies/.
+
+Global options:
+  --workspace <dir>   Directory holding movies/, output/, config.json, ... (default: .)
+  --config <path>     Config file (default: config.json)
+  --clips <n>         Number of clips to plan instead of a random 20-30
+  --seed <n>          Seed for the clip count and background music picks
+  --prompt <name>     Planning prompt template from prompts/{name}.txt (or a file path)
+  --style <name>      Narration style preset from config styles.presets, for every movie
+  --lang <codes>      Narration languages, comma-separated (e.g. es or en,fr); outputs get
+                      a _{code} suffix
+  -h, --help          Show this help";
+
+enum Command {
+    Run,
+    Plan { movie: String, out: Option<PathBuf> },
+    Render { movie: Option<String>, plan: PathBuf },
+    FetchSubs { movie: String },
+    Tts { text: String, out: PathBuf },
+    Vertical { input: PathBuf, output: PathBuf },
+}
+
+struct CliArgs {
+    workspace: Option<PathBuf>,
+    opts: GenerationOptions,
+    command: Command,
+}
+
+fn take_value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String> {
+    args.next().with_context(|| format!("{} needs a value", flag))
+}
+
+fn parse_args(raw: impl IntoIterator<Item = String>) -> Result<Option<CliArgs>> {
+    let mut args = raw.into_iter();
+    let mut workspace = None;
+    let mut opts = GenerationOptions::default();
+    let mut out = None;
+    let mut plan = None;
+    let mut positional: Vec<String> = Vec::new();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--workspace" => workspace = Some(PathBuf::from(take_value(&mut args, &arg)?)),
+            "--config" => opts.config_path = PathBuf::from(take_value(&mut args, &arg)?),
+            "--clips" => {
+                let n: i32 = take_value(&mut args, &arg)?
+                    .parse()
+                    .context("--clips expects a whole number")?;
+                anyhow::ensure!(n > 0, "--clips must be at least 1");
+                opts.num_clips = Some(n);
+            }
+            "--seed" => {
+                opts.seed = Some(
+                    take_value(&mut args, &arg)?
+                        .parse()
+                        .context("--seed expects a whole number")?,
+                )
+            }
+            "--prompt" => opts.prompt_template = Some(take_value(&mut args, &arg)?),
+            "--style" => opts.style = Some(take_value(&mut args, &arg)?),
+            "--lang" => {
+                let codes: Vec<String> = take_value(&mut args, &arg)?
+                    .split(',')
+                    .map(|code| code.trim().to_string())
+                    .filter(|code| !code.is_empty())
+                    .collect();
+                anyhow::ensure!(!codes.is_empty(), "--lang expects language codes such as es or en,fr");
+                opts.languages = Some(codes);
+            }
+            "--out" => out = Some(PathBuf::from(take_value(&mut args, &arg)?)),
+            "--plan-only" => opts.plan_only = true,
+            "--plan" => plan = Some(PathBuf::from(take_value(&mut args, &arg)?)),
+            flag if flag.starts_with("--") => anyhow::bail!("Unknown option: {}", flag),
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    let name = positional.next().unwrap_or_else(|| "run".to_string());
+    let mut operand = |what: &str| {
+        positional
+            .next()
+            .with_context(|| format!("{} needs {}", name, what))
+    };
+
+    let command = match name.as_str() {
+        "run" => Command::Run,
+        "plan" => Command::Plan {
+            movie: operand("<movie>")?,
+            out,
+        },
+        "render" => Command::Render {
+            movie: positional.next(),
+            plan: plan.context("render needs --plan <plan.json>")?,
+        },
+        "fetch-subs" => Command::FetchSubs {
+            movie: operand("<movie>")?,
+        },
+        "tts" => Command::Tts {
+            text: operand("<text>")?,
+            out: out.unwrap_or_else(|| PathBuf::from("tts_out.mp3")),
+        },
+        "vertical" => Command::Vertical {
+            input: PathBuf::from(operand("<in>")?),
+            output: PathBuf::from(operand("<out>")?),
+        },
+        other => anyhow::bail!("Unknown command: {}", other),
+    };
+
+    if let Some(extra) = positional.next() {
+        anyhow::bail!("Unexpected argument: {}", extra);
+    }
+
+    Ok(Some(CliArgs {
+        workspace,
+        opts,
+        command,
+    }))
+}
+
+async fn execute(cli: &CliArgs) -> Result<i32> {
+    let opts = &cli.opts;
+    match &cli.command {
+        Command::Run => generator::run_generation_with(opts).await,
+        Command::Plan { movie, out } => match generator::plan_single(opts, movie, out.as_deref()).await? {
+            Some(path) => {
+                println!("{}", path.display());
+                Ok(0)
+            }
+            None => Ok(1),
+        },
+        Command::Render { movie, plan } => {
+            let (plan, inputs) = generator::load_plan_file(plan).await?;
+            let movie = match (movie, &inputs) {
+                (Some(movie), _) => movie.clone(),
+                (None, Some(inputs)) => inputs.movie_file.clone(),
+                (None, None) => anyhow::bail!("render needs <movie> (the plan does not record one)"),
+            };
+            let ok = generator::render_single(opts, &movie, plan, inputs).await?;
+            Ok(if ok { 0 } else { 1 })
+        }
+        Command::FetchSubs { movie } => {
+            let (srt, attempts) = generator::fetch_subtitles_single(opts, movie).await?;
+            for attempt in &attempts {
+                if attempt.ok {
+                    eprintln!("[OK] {}", attempt.provider);
+                } else {
+                    eprintln!("[FAILED] {}: {}", attempt.provider, attempt.reason);
+                }
+            }
+            match srt {
+                Some(path) => {
+                    println!("{}", path.display());
+                    Ok(0)
+                }
+                None => Ok(1),
+            }
+        }
+        Command::Tts { text, out } => {
+            let ok = generator::synthesize_single(opts, text, out).await?;
+            if ok {
+                eprintln!("[OK] Narration written: {}", out.display());
+            }
+            Ok(if ok { 0 } else { 1 })
+        }
+        Command::Vertical { input, output } => {
+            let ok = generator::vertical_single(opts, input, output).await?;
+            Ok(if ok { 0 } else { 1 })
+        }
+    }
+}
+
+fn enter_workspace(dir: &Path) -> Result<()> {
+    std::env::set_current_dir(dir).with_context(|| format!("Failed to enter workspace: {}", dir.display()))
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
-    
+
+    let cli = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(cli)) => cli,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        Err(err) => {
+            eprintln!("[ERROR] {}\n\n{}", err, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if let Some(dir) = &cli.workspace {
+        enter_workspace(dir)?;
+    }
+
     // Initialize directories first
     init::ensure_directories().await?;
-    
+
     if !init::check_ffmpeg().await {
         eprintln!("[WARNING] FFmpeg not found in PATH. Please install FFmpeg.");
     }
-    
-    let code = run_generation().await?;
+
+    let code = execute(&cli).await?;
     std::process::exit(code);
 }
@@ -1,11 +1,18 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClipPlan {
-    pub start: i32,
-    pub end: i32,
+    /// Seconds into the movie, with millisecond precision.
+    pub start: f64,
+    pub end: f64,
     pub narration: String,
+    /// The planned `start`/`end` before they were moved onto a shot cut; absent when unmoved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub planned_start: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub planned_end: Option<f64>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -13,15 +20,359 @@ pub struct ClipPlanList {
     pub items: Vec<ClipPlan>,
 }
 
+/// What the planner was given, saved next to the clips so a reviewer can tell which
+/// subtitles, script and settings a plan came from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanProvenance {
+    pub movie_title: String,
+    pub movie_file: String,
+    pub planner: String,
+    pub num_clips: i32,
+    pub subtitles_file: String,
+    pub subtitles_hash: String,
+    /// IMSDb script sent as extra context; `None` when the plan came from subtitles alone.
+    #[serde(default)]
+    pub script_file: Option<String>,
+    /// Condensed beat list sent instead of raw subtitles for long films.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub beats_file: Option<String>,
+    /// Narration style preset; `None` for the built-in style.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    /// Target language code of the narration; `None` for the default English recap.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Prompt template file the request was rendered from; `None` for the built-in prompt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_template: Option<String>,
+    /// Required opening of the first narration, title filled in; empty when it was not checked.
+    #[serde(default)]
+    pub opening_line: String,
+    pub created_at: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct ClipPlanRoot {
     clips: Vec<ClipPlan>,
 }
 
+#[derive(Debug, Deserialize)]
+struct ClipPlanInputsRoot {
+    #[serde(default)]
+    inputs: Option<PlanProvenance>,
+}
+
+#[derive(Debug, Serialize)]
+struct ClipPlanRootRef<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    inputs: Option<&'a PlanProvenance>,
+    clips: &'a [ClipPlan],
+}
+
 impl ClipPlanList {
     pub fn from_json(text: &str) -> Result<Self> {
         let root: ClipPlanRoot =
             serde_json::from_str(text).with_context(|| "Failed to parse clip plan JSON")?;
         Ok(Self { items: root.clips })
     }
+
+    /// Strict JSON schema of the `{"clips":[...]}` shape, mirroring the fields of `ClipPlan` the planner writes.
+    pub fn json_schema() -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "clips": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "start": {"type": "number", "description": "Seconds into the movie"},
+                            "end": {"type": "number", "description": "Seconds into the movie"},
+                            "narration": {"type": "string"},
+                        },
+                        "required": ["start", "end", "narration"],
+                        "additionalProperties": false,
+                    },
+                },
+            },
+            "required": ["clips"],
+            "additionalProperties": false,
+        })
+    }
+
+    /// The `"inputs"` block of a saved plan, if it has one.
+    pub fn inputs_from_json(text: &str) -> Option<PlanProvenance> {
+        serde_json::from_str::<ClipPlanInputsRoot>(text).ok()?.inputs
+    }
+
+    /// Serializes back to the same `{"clips":[...]}` shape `from_json` accepts.
+    pub fn to_json(&self) -> Result<String> {
+        self.write_json(None)
+    }
+
+    /// Same as `to_json` plus an `"inputs"` block; `from_json` ignores it, so the file stays loadable.
+    pub fn to_json_with_inputs(&self, inputs: &PlanProvenance) -> Result<String> {
+        self.write_json(Some(inputs))
+    }
+
+    fn write_json(&self, inputs: Option<&PlanProvenance>) -> Result<String> {
+        serde_json::to_string_pretty(&ClipPlanRootRef {
+            inputs,
+            clips: &self.items,
+        })
+        .with_context(|| "Failed to serialize clip plan JSON")
+    }
+}
+
+/// Shortest and longest source range a single clip may cover, in seconds.
+pub const MIN_CLIP_DURATION: f64 = 8.0;
+pub const MAX_CLIP_DURATION: f64 = 20.0;
+/// Window for the summed source ranges of a whole plan, in seconds.
+pub const MIN_TOTAL_DURATION: f64 = 2.5 * 60.0;
+pub const MAX_TOTAL_DURATION: f64 = 4.5 * 60.0;
+
+/// What `ClipPlanList::validate_and_repair` changed, and what it could not fix.
+#[derive(Debug, Clone, Default)]
+pub struct PlanValidation {
+    pub fixes: Vec<String>,
+    pub problems: Vec<String>,
+}
+
+impl PlanValidation {
+    pub fn is_clean(&self) -> bool {
+        self.fixes.is_empty() && self.problems.is_empty()
+    }
+}
+
+impl ClipPlanList {
+    pub fn total_duration(&self) -> f64 {
+        self.items.iter().map(|c| c.end - c.start).sum()
+    }
+
+    /// Checks ordering, overlap, per-clip and total duration and the movie bounds, repairing what it
+    /// can by sorting, merging, clamping and dropping clips. `movie_duration` is the probed length
+    /// of the source, if known.
+    pub fn validate_and_repair(&mut self, movie_duration: Option<f64>) -> PlanValidation {
+        let mut report = PlanValidation::default();
+        let limit = movie_duration.filter(|d| *d > 0.0).unwrap_or(f64::INFINITY);
+        let opening = self.items.first().map(|c| c.narration.clone());
+
+        // Unusable ranges and ranges outside the movie.
+        let mut kept = Vec::with_capacity(self.items.len());
+        for clip in self.items.drain(..) {
+            let label = format!("{:.3}-{:.3}", clip.start, clip.end);
+            if !clip.start.is_finite() || !clip.end.is_finite() || clip.narration.trim().is_empty() {
+                report.fixes.push(format!("dropped {} (invalid times or empty narration)", label));
+            } else if clip.start <= 0.0 {
+                report.fixes.push(format!("dropped {} (starts at or before 0)", label));
+            } else if clip.end <= clip.start {
+                report.fixes.push(format!("dropped {} (end is not after start)", label));
+            } else if clip.start >= limit {
+                report.fixes.push(format!("dropped {} (starts after the movie ends at {:.3})", label, limit));
+            } else if clip.end > limit {
+                report.fixes.push(format!("clamped {} to end at the movie end {:.3}", label, limit));
+                kept.push(ClipPlan { end: limit, ..clip });
+            } else {
+                kept.push(clip);
+            }
+        }
+
+        if kept.windows(2).any(|w| w[1].start < w[0].start) {
+            kept.sort_by(|a, b| a.start.total_cmp(&b.start));
+            report.fixes.push("sorted clips by start time".to_string());
+        }
+
+        // Overlaps: merge when the union still fits one clip (keeping the narration written for the
+        // longer range), otherwise start the later clip where the earlier one ends.
+        let mut merged: Vec<ClipPlan> = Vec::with_capacity(kept.len());
+        for clip in kept {
+            let Some(prev) = merged.last_mut() else {
+                merged.push(clip);
+                continue;
+            };
+            if clip.start >= prev.end {
+                merged.push(clip);
+                continue;
+            }
+            let union_end = prev.end.max(clip.end);
+            if union_end - prev.start <= MAX_CLIP_DURATION {
+                report.fixes.push(format!(
+                    "merged overlapping {:.3}-{:.3} and {:.3}-{:.3}",
+                    prev.start, prev.end, clip.start, clip.end
+                ));
+                if clip.end - clip.start > prev.end - prev.start {
+                    prev.narration = clip.narration;
+                }
+                prev.end = union_end;
+            } else if clip.end > prev.end {
+                report.fixes.push(format!(
+                    "moved start of {:.3}-{:.3} to {:.3} (overlapped the previous clip)",
+                    clip.start, clip.end, prev.end
+                ));
+                let start = prev.end;
+                merged.push(ClipPlan { start, ..clip });
+            } else {
+                report.fixes.push(format!(
+                    "dropped {:.3}-{:.3} (inside {:.3}-{:.3})",
+                    clip.start, clip.end, prev.start, prev.end
+                ));
+            }
+        }
+
+        // Per-clip bounds: trim long clips, grow short ones into free space around them.
+        for i in 0..merged.len() {
+            let dur = merged[i].end - merged[i].start;
+            if dur > MAX_CLIP_DURATION {
+                let clip = &mut merged[i];
+                report.fixes.push(format!(
+                    "clamped {:.3}-{:.3} to {:.0}s",
+                    clip.start, clip.end, MAX_CLIP_DURATION
+                ));
+                clip.end = clip.start + MAX_CLIP_DURATION;
+            } else if dur < MIN_CLIP_DURATION {
+                let next_start = merged.get(i + 1).map(|c| c.start).unwrap_or(limit);
+                let prev_end = if i > 0 { merged[i - 1].end } else { 0.0 };
+                let clip = &mut merged[i];
+                let label = format!("{:.3}-{:.3}", clip.start, clip.end);
+                clip.end = (clip.start + MIN_CLIP_DURATION).min(next_start);
+                let missing = MIN_CLIP_DURATION - (clip.end - clip.start);
+                if missing > 0.0 {
+                    clip.start = (clip.start - missing).max(prev_end);
+                }
+                report.fixes.push(format!("extended {} to {:.3}-{:.3}", label, clip.start, clip.end));
+            }
+        }
+        merged.retain(|clip| {
+            let short = clip.end - clip.start < MIN_CLIP_DURATION - 1e-6 || clip.start <= 0.0;
+            if short {
+                report.fixes.push(format!(
+                    "dropped {:.3}-{:.3} (shorter than {:.0}s with no room to extend)",
+                    clip.start, clip.end, MIN_CLIP_DURATION
+                ));
+            }
+            !short
+        });
+
+        // Total window: shave the longest clips down toward the minimum, then report what is left.
+        let mut excess = merged.iter().map(|c| c.end - c.start).sum::<f64>() - MAX_TOTAL_DURATION;
+        if excess > 0.0 {
+            let mut order: Vec<usize> = (0..merged.len()).collect();
+            order.sort_by(|&a, &b| {
+                let da = merged[a].end - merged[a].start;
+                let db = merged[b].end - merged[b].start;
+                db.total_cmp(&da)
+            });
+            let mut shaved = 0.0;
+            for idx in order {
+                if excess <= 0.0 {
+                    break;
+                }
+                let clip = &mut merged[idx];
+                let cut = (clip.end - clip.start - MIN_CLIP_DURATION).min(excess);
+                if cut > 0.0 {
+                    clip.end -= cut;
+                    excess -= cut;
+                    shaved += cut;
+                }
+            }
+            if shaved > 0.0 {
+                report.fixes.push(format!("shortened clips by {:.1}s to fit the total duration", shaved));
+            }
+        }
+
+        self.items = merged;
+        if opening.is_some() && self.items.first().map(|c| &c.narration) != opening.as_ref() {
+            report.problems.push("the first clip changed, so its opening line may be lost".to_string());
+        }
+        let total = self.total_duration();
+        if self.items.is_empty() {
+            report.problems.push("no usable clips left".to_string());
+        } else if total < MIN_TOTAL_DURATION {
+            report.problems.push(format!(
+                "total duration {:.1}s is below the minimum {:.0}s",
+                total, MIN_TOTAL_DURATION
+            ));
+        } else if total > MAX_TOTAL_DURATION {
+            report.problems.push(format!(
+                "total duration {:.1}s is above the maximum {:.0}s",
+                total, MAX_TOTAL_DURATION
+            ));
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(start: f64, end: f64, narration: &str) -> ClipPlan {
+        ClipPlan {
+            start,
+            end,
+            narration: narration.to_string(),
+            planned_start: None,
+            planned_end: None,
+        }
+    }
+
+    fn plan(items: Vec<ClipPlan>) -> ClipPlanList {
+        ClipPlanList { items }
+    }
+
+    fn ranges(plan: &ClipPlanList) -> Vec<(f64, f64)> {
+        plan.items.iter().map(|c| (c.start, c.end)).collect()
+    }
+
+    #[test]
+    fn overlapping_clips_merge_and_keep_the_longer_narration() {
+        let mut list = plan(vec![clip(10.0, 18.0, "opening"), clip(40.0, 48.0, "short"), clip(44.0, 58.0, "long")]);
+        list.validate_and_repair(None);
+        assert_eq!(ranges(&list), vec![(10.0, 18.0), (40.0, 58.0)]);
+        assert_eq!(list.items[1].narration, "long");
+    }
+
+    #[test]
+    fn replacing_the_first_narration_is_reported() {
+        let mut list = plan(vec![clip(10.0, 18.0, "Here we go"), clip(12.0, 25.0, "later")]);
+        let report = list.validate_and_repair(None);
+        assert_eq!(list.items[0].narration, "later");
+        assert!(report.problems.iter().any(|p| p.contains("opening line")));
+    }
+
+    #[test]
+    fn clip_inside_a_long_clip_is_dropped() {
+        let mut list = plan(vec![clip(100.0, 125.0, "outer"), clip(105.0, 110.0, "inner")]);
+        let report = list.validate_and_repair(None);
+        assert_eq!(ranges(&list), vec![(100.0, 100.0 + MAX_CLIP_DURATION)]);
+        assert_eq!(list.items[0].narration, "outer");
+        assert!(report.fixes.iter().any(|f| f.starts_with("dropped 105.000-110.000")));
+    }
+
+    #[test]
+    fn short_clips_extend_into_free_space() {
+        let mut list = plan(vec![clip(100.0, 104.0, "a"), clip(200.0, 204.0, "b"), clip(206.0, 220.0, "c")]);
+        list.validate_and_repair(None);
+        assert_eq!(ranges(&list), vec![(100.0, 108.0), (198.0, 206.0), (206.0, 220.0)]);
+    }
+
+    #[test]
+    fn long_plans_are_shaved_to_the_total_maximum() {
+        let items = (0..15).map(|i| clip(100.0 + i as f64 * 30.0, 120.0 + i as f64 * 30.0, "n")).collect();
+        let mut list = plan(items);
+        let report = list.validate_and_repair(None);
+        assert_eq!(list.items.len(), 15);
+        assert!((list.total_duration() - MAX_TOTAL_DURATION).abs() < 1e-6);
+        assert!(list.items.iter().all(|c| c.end - c.start >= MIN_CLIP_DURATION));
+        assert!(report.problems.is_empty());
+    }
+
+    #[test]
+    fn clips_are_clamped_to_the_movie_end() {
+        let mut list = plan(vec![clip(100.0, 115.0, "a"), clip(120.0, 130.0, "b")]);
+        let report = list.validate_and_repair(Some(110.0));
+        assert_eq!(ranges(&list), vec![(100.0, 110.0)]);
+        assert!(report.fixes.iter().any(|f| f.starts_with("clamped 100.000-115.000")));
+        assert!(report.fixes.iter().any(|f| f.starts_with("dropped 120.000-130.000")));
+    }
 }
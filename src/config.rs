@@ -1,5 +1,6 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::Path;
 use tokio::fs;
 
@@ -15,6 +16,501 @@ pub struct Config {
     #[serde(rename = "eleven_model_id")]
     #[serde(default = "default_model_id")]
     pub eleven_model_id: String,
+    #[serde(default)]
+    pub planner: PlannerConfig,
+    #[serde(default)]
+    pub tts: TtsConfig,
+    #[serde(default)]
+    pub subtitles: SubtitleConfig,
+    #[serde(default)]
+    pub concurrency: ConcurrencyConfig,
+    #[serde(default)]
+    pub styles: StyleConfig,
+    #[serde(default)]
+    pub languages: LanguageConfig,
+    #[serde(default)]
+    pub captions: CaptionConfig,
+    #[serde(default)]
+    pub narration_subtitles: NarrationSubtitleConfig,
+    #[serde(default)]
+    pub vertical: VerticalConfig,
+    #[serde(default)]
+    pub cut_snap: CutSnapConfig,
+    #[serde(default)]
+    pub letterbox: LetterboxConfig,
+}
+
+/// How the narration sounds: prompt instructions, length, voice and music.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StylePreset {
+    #[serde(default = "default_style_instructions")]
+    pub instructions: String,
+    #[serde(default = "default_style_min_words")]
+    pub min_words: u32,
+    #[serde(default = "default_style_max_words")]
+    pub max_words: u32,
+    /// ElevenLabs voice for this style; empty keeps `eleven_voice_id`.
+    #[serde(default)]
+    pub voice_id: String,
+    /// Subfolder of `backgroundmusic/` to pick tracks from; empty uses the whole folder.
+    #[serde(default)]
+    pub bgm_mood: String,
+}
+
+impl Default for StylePreset {
+    fn default() -> Self {
+        Self {
+            instructions: default_style_instructions(),
+            min_words: default_style_min_words(),
+            max_words: default_style_max_words(),
+            voice_id: String::new(),
+            bgm_mood: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StyleConfig {
+    /// Preset used when neither the run nor the movie picks one; empty means the built-in style.
+    #[serde(default)]
+    pub default: String,
+    /// Movie title -> preset name.
+    #[serde(default)]
+    pub per_movie: BTreeMap<String, String>,
+    #[serde(default = "default_style_presets")]
+    pub presets: BTreeMap<String, StylePreset>,
+}
+
+impl Default for StyleConfig {
+    fn default() -> Self {
+        Self {
+            default: String::new(),
+            per_movie: BTreeMap::new(),
+            presets: default_style_presets(),
+        }
+    }
+}
+
+/// Prompt wording and voice settings for one narration language.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LanguageProfile {
+    /// Language name used in the planning prompt; empty uses the code.
+    #[serde(default)]
+    pub name: String,
+    /// ElevenLabs voice (or espeak-ng voice); empty keeps the configured one.
+    #[serde(default)]
+    pub voice_id: String,
+    /// ElevenLabs model (or piper `.onnx` model); empty picks a multilingual ElevenLabs model
+    /// when the configured one is English-only.
+    #[serde(default)]
+    pub model_id: String,
+    /// Required start of the first narration in this language; empty disables the check
+    /// (English falls back to `planner.opening_line`).
+    #[serde(default)]
+    pub opening_line: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageConfig {
+    /// Language codes to narrate every movie in, one recap each with `_{code}` appended to the
+    /// output names. Empty keeps a single English recap with unsuffixed names.
+    #[serde(default)]
+    pub targets: Vec<String>,
+    #[serde(default = "default_language_profiles")]
+    pub profiles: BTreeMap<String, LanguageProfile>,
+}
+
+impl Default for LanguageConfig {
+    fn default() -> Self {
+        Self {
+            targets: Vec::new(),
+            profiles: default_language_profiles(),
+        }
+    }
+}
+
+/// `en`, `en-US`, `en_GB`, ...
+pub fn is_english(code: &str) -> bool {
+    let code = code.to_ascii_lowercase();
+    code == "en" || code.starts_with("en-") || code.starts_with("en_")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptionPosition {
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// Word-by-word narration captions burned into the vertical output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptionConfig {
+    #[serde(default = "default_captions_enabled")]
+    pub enabled: bool,
+    /// Font file handed to libass; `font_name` must be its family name.
+    #[serde(default = "default_caption_font_file")]
+    pub font_file: String,
+    #[serde(default = "default_caption_font_name")]
+    pub font_name: String,
+    /// Font size on a 1080x1920 canvas, scaled to the actual output size.
+    #[serde(default = "default_caption_font_size")]
+    pub font_size: u32,
+    #[serde(default = "default_caption_position")]
+    pub position: CaptionPosition,
+    /// Distance from the top or bottom edge on the 1080x1920 canvas; ignored for `middle`.
+    #[serde(default = "default_caption_margin")]
+    pub margin: u32,
+    #[serde(default = "default_caption_words_per_line")]
+    pub words_per_line: usize,
+    /// `#RRGGBB` colours for the text, the word being spoken and the outline.
+    #[serde(default = "default_caption_color")]
+    pub color: String,
+    #[serde(default = "default_caption_highlight_color")]
+    pub highlight_color: String,
+    #[serde(default = "default_caption_outline_color")]
+    pub outline_color: String,
+    #[serde(default = "default_caption_outline")]
+    pub outline: u32,
+}
+
+impl Default for CaptionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_captions_enabled(),
+            font_file: default_caption_font_file(),
+            font_name: default_caption_font_name(),
+            font_size: default_caption_font_size(),
+            position: default_caption_position(),
+            margin: default_caption_margin(),
+            words_per_line: default_caption_words_per_line(),
+            color: default_caption_color(),
+            highlight_color: default_caption_highlight_color(),
+            outline_color: default_caption_outline_color(),
+            outline: default_caption_outline(),
+        }
+    }
+}
+
+/// SRT/VTT files of the narration written next to the horizontal output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NarrationSubtitleConfig {
+    #[serde(default = "default_narration_subtitles_enabled")]
+    pub enabled: bool,
+    /// Also mux the SRT into `output/{title}.mp4` as a soft `mov_text` track.
+    #[serde(default)]
+    pub mux: bool,
+    /// Longest cue; longer sentences are split, and cues over half this are wrapped onto two lines.
+    #[serde(default = "default_narration_subtitles_max_cue_chars")]
+    pub max_cue_chars: usize,
+}
+
+impl Default for NarrationSubtitleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_narration_subtitles_enabled(),
+            mux: false,
+            max_cue_chars: default_narration_subtitles_max_cue_chars(),
+        }
+    }
+}
+
+/// Moves planned clip boundaries onto nearby shot cuts so clips do not open or close on a
+/// few frames of the neighbouring shot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CutSnapConfig {
+    #[serde(default = "default_cut_snap_enabled")]
+    pub enabled: bool,
+    /// Furthest a boundary may move, in seconds.
+    #[serde(default = "default_cut_snap_tolerance_secs")]
+    pub tolerance_secs: f64,
+    /// ffmpeg scene-change score (0-1) above which a frame starts a new shot.
+    #[serde(default = "default_cut_snap_threshold")]
+    pub threshold: f64,
+}
+
+impl Default for CutSnapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_cut_snap_enabled(),
+            tolerance_secs: default_cut_snap_tolerance_secs(),
+            threshold: default_cut_snap_threshold(),
+        }
+    }
+}
+
+/// Crops black bars baked into the source out of every render.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LetterboxConfig {
+    #[serde(default = "default_letterbox_enabled")]
+    pub enabled: bool,
+    /// Brightest pixel value (0-255) still counted as part of a bar.
+    #[serde(default = "default_letterbox_black_level")]
+    pub black_level: u32,
+}
+
+impl Default for LetterboxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_letterbox_enabled(),
+            black_level: default_letterbox_black_level(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerticalLayout {
+    /// The whole frame over a blurred, darkened copy of itself filling the 9:16 canvas.
+    Blur,
+    /// A 60% wide crop, letterboxed with black bars.
+    Pad,
+    /// A full-height 9:16 crop; no bars, but most of the width is lost.
+    Crop,
+}
+
+/// How the 9:16 render frames the horizontal video.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerticalConfig {
+    #[serde(default = "default_vertical_layout")]
+    pub layout: VerticalLayout,
+    /// Gaussian blur sigma of the `blur` background, measured at quarter resolution.
+    #[serde(default = "default_vertical_background_blur")]
+    pub background_blur: f64,
+    /// Brightness multiplier for the `blur` background (1.0 leaves it as is).
+    #[serde(default = "default_vertical_background_brightness")]
+    pub background_brightness: f64,
+    /// Move the crop to follow motion and detail instead of keeping it centred (`pad` and `crop`).
+    #[serde(default = "default_vertical_reframe")]
+    pub reframe: bool,
+    /// Frames per second sampled for the reframing analysis.
+    #[serde(default = "default_vertical_analysis_fps")]
+    pub analysis_fps: f64,
+    /// Width the sampled frames are scaled down to before analysis.
+    #[serde(default = "default_vertical_analysis_width")]
+    pub analysis_width: u32,
+    /// Seconds over which the crop position is averaged.
+    #[serde(default = "default_vertical_smoothing_secs")]
+    pub smoothing_secs: f64,
+    /// Fastest pan, in frame widths per second.
+    #[serde(default = "default_vertical_max_pan_speed")]
+    pub max_pan_speed: f64,
+}
+
+impl Default for VerticalConfig {
+    fn default() -> Self {
+        Self {
+            layout: default_vertical_layout(),
+            background_blur: default_vertical_background_blur(),
+            background_brightness: default_vertical_background_brightness(),
+            reframe: default_vertical_reframe(),
+            analysis_fps: default_vertical_analysis_fps(),
+            analysis_width: default_vertical_analysis_width(),
+            smoothing_secs: default_vertical_smoothing_secs(),
+            max_pan_speed: default_vertical_max_pan_speed(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConcurrencyConfig {
+    /// Movies processed at the same time, each in its own `clips/{title}/` directory.
+    #[serde(default = "default_parallel_movies")]
+    pub movies: usize,
+    /// Simultaneous narration (TTS) requests.
+    #[serde(default = "default_tts_requests")]
+    pub tts_requests: usize,
+    /// Simultaneous ffmpeg clip renders.
+    #[serde(default = "default_ffmpeg_processes")]
+    pub ffmpeg_processes: usize,
+}
+
+impl Default for ConcurrencyConfig {
+    fn default() -> Self {
+        Self {
+            movies: default_parallel_movies(),
+            tts_requests: default_tts_requests(),
+            ffmpeg_processes: default_ffmpeg_processes(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubtitleProviderKind {
+    /// `{title}.srt` in one of `local_dirs`.
+    Local,
+    /// A subtitle stream muxed into the movie file.
+    Embedded,
+    Subf2m,
+    /// OpenSubtitles-compatible REST API.
+    Opensubtitles,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenSubtitlesConfig {
+    #[serde(default = "default_opensubtitles_base_url")]
+    pub base_url: String,
+    #[serde(default)]
+    pub api_key: String,
+    #[serde(default = "default_opensubtitles_user_agent")]
+    pub user_agent: String,
+}
+
+impl Default for OpenSubtitlesConfig {
+    fn default() -> Self {
+        Self {
+            base_url: default_opensubtitles_base_url(),
+            api_key: String::new(),
+            user_agent: default_opensubtitles_user_agent(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubtitleConfig {
+    /// Fallback chain, tried in order until one provider yields an SRT.
+    #[serde(default = "default_subtitle_providers")]
+    pub providers: Vec<SubtitleProviderKind>,
+    #[serde(default = "default_subtitle_local_dirs")]
+    pub local_dirs: Vec<String>,
+    #[serde(default)]
+    pub opensubtitles: OpenSubtitlesConfig,
+}
+
+impl Default for SubtitleConfig {
+    fn default() -> Self {
+        Self {
+            providers: default_subtitle_providers(),
+            local_dirs: default_subtitle_local_dirs(),
+            opensubtitles: OpenSubtitlesConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlannerBackend {
+    /// OpenAI Responses API (`/v1/responses`).
+    OpenaiResponses,
+    /// Any OpenAI-compatible `/v1/chat/completions` server.
+    ChatCompletions,
+}
+
+/// When subtitles are first condensed into timestamped beats before planning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanChunking {
+    /// Only when the subtitles would not fit the planning prompt.
+    Auto,
+    Always,
+    /// Always send raw subtitles, truncated if necessary.
+    Never,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannerConfig {
+    #[serde(default = "default_planner_backend")]
+    pub backend: PlannerBackend,
+    #[serde(default = "default_planner_base_url")]
+    pub base_url: String,
+    #[serde(default = "default_planner_model")]
+    pub model: String,
+    /// Overrides `open_api_key` for the planner; may stay empty for local servers.
+    #[serde(default)]
+    pub api_key: String,
+    /// Extra requests that send the model its previous plan and the rule violations found in it.
+    #[serde(default = "default_planner_feedback_rounds")]
+    pub feedback_rounds: u32,
+    #[serde(default = "default_planner_chunking")]
+    pub chunking: PlanChunking,
+    /// Subtitle characters summarised per beat request.
+    #[serde(default = "default_planner_beat_window_chars")]
+    pub beat_window_chars: usize,
+    /// Template name in `prompts/` (or a path to a file); empty uses the built-in prompt.
+    #[serde(default)]
+    pub prompt_template: String,
+    /// Required start of the first narration, `{{title}}` allowed; empty disables the check.
+    #[serde(default = "default_planner_opening_line")]
+    pub opening_line: String,
+}
+
+impl Default for PlannerConfig {
+    fn default() -> Self {
+        Self {
+            backend: default_planner_backend(),
+            base_url: default_planner_base_url(),
+            model: default_planner_model(),
+            api_key: String::new(),
+            feedback_rounds: default_planner_feedback_rounds(),
+            chunking: default_planner_chunking(),
+            beat_window_chars: default_planner_beat_window_chars(),
+            prompt_template: String::new(),
+            opening_line: default_planner_opening_line(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TtsBackend {
+    Elevenlabs,
+    /// Offline engine on this machine; see [`LocalTtsConfig`].
+    Local,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LocalTtsEngine {
+    Piper,
+    EspeakNg,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalTtsConfig {
+    #[serde(default = "default_local_engine")]
+    pub engine: LocalTtsEngine,
+    /// Executable to run; empty means `piper` / `espeak-ng` from PATH.
+    #[serde(default)]
+    pub binary: String,
+    /// piper voice model (`.onnx`); ignored by espeak-ng.
+    #[serde(default)]
+    pub model: String,
+    /// espeak-ng voice name; ignored by piper.
+    #[serde(default = "default_local_voice")]
+    pub voice: String,
+    /// espeak-ng speaking rate in words per minute.
+    #[serde(default = "default_local_rate_wpm")]
+    pub rate_wpm: u32,
+}
+
+impl Default for LocalTtsConfig {
+    fn default() -> Self {
+        Self {
+            engine: default_local_engine(),
+            binary: String::new(),
+            model: String::new(),
+            voice: default_local_voice(),
+            rate_wpm: default_local_rate_wpm(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TtsConfig {
+    #[serde(default = "default_tts_backend")]
+    pub backend: TtsBackend,
+    #[serde(default)]
+    pub local: LocalTtsConfig,
+}
+
+impl Default for TtsConfig {
+    fn default() -> Self {
+        Self {
+            backend: default_tts_backend(),
+            local: LocalTtsConfig::default(),
+        }
+    }
 }
 
 fn default_voice_id() -> String {
@@ -25,7 +521,252 @@ fn default_model_id() -> String {
     "eleven_multilingual_v2".to_string()
 }
 
+fn default_parallel_movies() -> usize {
+    1
+}
+
+fn default_tts_requests() -> usize {
+    4
+}
+
+fn default_ffmpeg_processes() -> usize {
+    2
+}
+
+fn default_subtitle_providers() -> Vec<SubtitleProviderKind> {
+    vec![
+        SubtitleProviderKind::Local,
+        SubtitleProviderKind::Embedded,
+        SubtitleProviderKind::Subf2m,
+        SubtitleProviderKind::Opensubtitles,
+    ]
+}
+
+fn default_subtitle_local_dirs() -> Vec<String> {
+    vec!["subtitles".to_string(), "movies".to_string()]
+}
+
+fn default_opensubtitles_base_url() -> String {
+    "https://api.opensubtitles.com/api/v1".to_string()
+}
+
+fn default_opensubtitles_user_agent() -> String {
+    "ai-movie-shorts v0.1.0".to_string()
+}
+
+fn default_tts_backend() -> TtsBackend {
+    TtsBackend::Elevenlabs
+}
+
+fn default_local_engine() -> LocalTtsEngine {
+    LocalTtsEngine::EspeakNg
+}
+
+fn default_local_voice() -> String {
+    "en-us".to_string()
+}
+
+fn default_local_rate_wpm() -> u32 {
+    170
+}
+
+fn default_planner_backend() -> PlannerBackend {
+    PlannerBackend::OpenaiResponses
+}
+
+fn default_planner_base_url() -> String {
+    "https://api.openai.com/v1".to_string()
+}
+
+fn default_planner_feedback_rounds() -> u32 {
+    2
+}
+
+fn default_planner_chunking() -> PlanChunking {
+    PlanChunking::Auto
+}
+
+fn default_planner_beat_window_chars() -> usize {
+    60_000
+}
+
+fn default_style_instructions() -> String {
+    "casual commentator vibe".to_string()
+}
+
+fn default_style_min_words() -> u32 {
+    20
+}
+
+fn default_style_max_words() -> u32 {
+    35
+}
+
+fn default_style_presets() -> BTreeMap<String, StylePreset> {
+    let preset = |instructions: &str, min_words, max_words, bgm_mood: &str| StylePreset {
+        instructions: instructions.to_string(),
+        min_words,
+        max_words,
+        voice_id: String::new(),
+        bgm_mood: bgm_mood.to_string(),
+    };
+    BTreeMap::from([
+        (
+            "dramatic-trailer".to_string(),
+            preset("booming movie-trailer voice, big stakes, short punchy lines, builds suspense", 15, 28, "epic"),
+        ),
+        (
+            "deadpan".to_string(),
+            preset("dry deadpan comedy, understated jokes about the characters' choices, never mean", 20, 35, "quirky"),
+        ),
+        (
+            "noir".to_string(),
+            preset("hard-boiled 1940s noir narrator, first person, rain-soaked metaphors", 22, 38, "noir"),
+        ),
+        (
+            "kid-friendly".to_string(),
+            preset("warm and upbeat, simple words for ages 8-12, no violence or scary details", 18, 30, "playful"),
+        ),
+    ])
+}
+
+fn default_captions_enabled() -> bool {
+    true
+}
+
+fn default_caption_font_file() -> String {
+    "resources/Inter-Regular.ttf".to_string()
+}
+
+fn default_caption_font_name() -> String {
+    "Inter".to_string()
+}
+
+fn default_caption_font_size() -> u32 {
+    84
+}
+
+fn default_caption_position() -> CaptionPosition {
+    CaptionPosition::Bottom
+}
+
+fn default_caption_margin() -> u32 {
+    360
+}
+
+fn default_caption_words_per_line() -> usize {
+    3
+}
+
+fn default_caption_color() -> String {
+    "#FFFFFF".to_string()
+}
+
+fn default_caption_highlight_color() -> String {
+    "#FFD400".to_string()
+}
+
+fn default_caption_outline_color() -> String {
+    "#000000".to_string()
+}
+
+fn default_caption_outline() -> u32 {
+    5
+}
+
+fn default_narration_subtitles_enabled() -> bool {
+    true
+}
+
+fn default_narration_subtitles_max_cue_chars() -> usize {
+    84
+}
+
+fn default_cut_snap_enabled() -> bool {
+    true
+}
+
+fn default_cut_snap_tolerance_secs() -> f64 {
+    0.5
+}
+
+fn default_cut_snap_threshold() -> f64 {
+    0.3
+}
+
+fn default_letterbox_enabled() -> bool {
+    true
+}
+
+fn default_letterbox_black_level() -> u32 {
+    24
+}
+
+fn default_vertical_layout() -> VerticalLayout {
+    VerticalLayout::Pad
+}
+
+fn default_vertical_background_blur() -> f64 {
+    12.0
+}
+
+fn default_vertical_background_brightness() -> f64 {
+    0.5
+}
+
+fn default_vertical_reframe() -> bool {
+    true
+}
+
+fn default_vertical_analysis_fps() -> f64 {
+    8.0
+}
+
+fn default_vertical_analysis_width() -> u32 {
+    192
+}
+
+fn default_vertical_smoothing_secs() -> f64 {
+    1.0
+}
+
+fn default_vertical_max_pan_speed() -> f64 {
+    0.3
+}
+
+fn default_language_profiles() -> BTreeMap<String, LanguageProfile> {
+    let profile = |name: &str, opening_line: &str| LanguageProfile {
+        name: name.to_string(),
+        opening_line: opening_line.to_string(),
+        ..Default::default()
+    };
+    BTreeMap::from([
+        ("de".to_string(), profile("German", "Los geht's, schauen wir uns den Film {{title}} an.")),
+        ("en".to_string(), profile("English", "")),
+        ("es".to_string(), profile("Spanish", "Vamos allá, repasemos la película {{title}}.")),
+        ("fr".to_string(), profile("French", "C'est parti, revoyons le film {{title}}.")),
+        ("it".to_string(), profile("Italian", "Ci siamo, ripercorriamo il film {{title}}.")),
+        ("pt".to_string(), profile("Portuguese", "Vamos lá, vamos rever o filme {{title}}.")),
+    ])
+}
+
+fn default_planner_opening_line() -> String {
+    crate::planner::DEFAULT_OPENING_LINE.to_string()
+}
+
+fn default_planner_model() -> String {
+    "gpt-5.2".to_string()
+}
+
 impl Config {
+    pub fn planner_api_key(&self) -> &str {
+        if self.planner.api_key.is_empty() {
+            &self.openai_key
+        } else {
+            &self.planner.api_key
+        }
+    }
+
     pub async fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
         // Create default config if it doesn't exist
         Self::create_default_if_missing(&path).await?;
@@ -35,10 +776,10 @@ impl Config {
             .with_context(|| format!("Failed to read config: {}", path.as_ref().display()))?;
         let config: Config = serde_json::from_str(&content)?;
         
-        if config.openai_key.is_empty() {
+        if config.planner.backend == PlannerBackend::OpenaiResponses && config.planner_api_key().is_empty() {
             anyhow::bail!("config.json: open_api_key is empty. Please add your OpenAI API key.");
         }
-        if config.elevenlabs_key.is_empty() {
+        if config.tts.backend == TtsBackend::Elevenlabs && config.elevenlabs_key.is_empty() {
             anyhow::bail!("config.json: elevenlabs_api_key is empty. Please add your ElevenLabs API key.");
         }
         
@@ -52,6 +793,17 @@ impl Config {
                 elevenlabs_key: String::new(),
                 eleven_voice_id: default_voice_id(),
                 eleven_model_id: default_model_id(),
+                planner: PlannerConfig::default(),
+                tts: TtsConfig::default(),
+                subtitles: SubtitleConfig::default(),
+                concurrency: ConcurrencyConfig::default(),
+                styles: StyleConfig::default(),
+                languages: LanguageConfig::default(),
+                captions: CaptionConfig::default(),
+                narration_subtitles: NarrationSubtitleConfig::default(),
+                vertical: VerticalConfig::default(),
+                cut_snap: CutSnapConfig::default(),
+                letterbox: LetterboxConfig::default(),
             };
             
             let json = serde_json::to_string_pretty(&default_config)?;
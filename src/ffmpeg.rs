@@ -1,10 +1,21 @@
+use crate::config::{VerticalConfig, VerticalLayout};
 use crate::{logi, logw};
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 use std::path::Path;
+use std::process::Stdio;
+use tokio::io::AsyncReadExt;
 use tokio::process::Command;
 
 const MAX_VIDEO_SPEEDUP: f64 = 1.75;
 
+/// Points across the movie probed for black bars, and seconds analysed at each.
+const LETTERBOX_SAMPLES: usize = 8;
+const LETTERBOX_SAMPLE_SECS: f64 = 2.0;
+
+/// Bars thinner than this share of the frame are left alone.
+const LETTERBOX_MIN_BAR_FRACTION: f64 = 0.02;
+
 async fn run_cmd(args: &[String]) -> Result<()> {
     if args.is_empty() {
         return Ok(());
@@ -62,6 +73,35 @@ pub async fn ffprobe_video_dimensions(path: &Path) -> Result<(i32, i32)> {
     Ok((w, h))
 }
 
+pub async fn ffprobe_has_video_stream(path: &Path) -> Result<bool> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "v",
+            "-show_entries",
+            "stream=codec_type:stream_disposition=attached_pic",
+            "-of",
+            "csv=p=0",
+        ])
+        .arg(path)
+        .output()
+        .await
+        .context("ffprobe execution failed")?;
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("ffprobe failed"));
+    }
+
+    // Cover art is exposed as a video stream flagged attached_pic=1; it doesn't count.
+    let text = String::from_utf8_lossy(&output.stdout);
+    Ok(text
+        .lines()
+        .map(|line| line.trim())
+        .any(|line| line.starts_with("video") && !line.ends_with(",1")))
+}
+
 pub async fn ffprobe_duration_seconds(path: &Path) -> Result<f64> {
     let output = Command::new("ffprobe")
         .args([
@@ -89,15 +129,294 @@ pub async fn ffprobe_duration_seconds(path: &Path) -> Result<f64> {
     Ok(duration)
 }
 
+/// The picture area of a frame with baked-in black bars, in source pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CropRect {
+    pub w: i32,
+    pub h: i32,
+    pub x: i32,
+    pub y: i32,
+}
+
+impl CropRect {
+    pub fn filter(&self) -> String {
+        format!("crop={}:{}:{}:{}", self.w, self.h, self.x, self.y)
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.split(':').map(|v| v.parse::<i32>().ok());
+        let rect = Self {
+            w: parts.next()??,
+            h: parts.next()??,
+            x: parts.next()??,
+            y: parts.next()??,
+        };
+        (rect.w > 0 && rect.h > 0 && rect.x >= 0 && rect.y >= 0).then_some(rect)
+    }
+}
+
+/// Runs `cropdetect` on a few stretches spread over `duration` seconds of `path` and returns
+/// the area inside any black bars, or `None` when the picture fills the frame. Samples are
+/// combined so a dark scene cannot shrink the area; `black_level` (0-255) is the brightest
+/// value still counted as bar.
+pub async fn ffmpeg_detect_letterbox(path: &Path, duration: f64, black_level: u32) -> Result<Option<CropRect>> {
+    let (w, h) = ffprobe_video_dimensions(path).await?;
+    let mut found: Option<(i32, i32, i32, i32)> = None;
+    for i in 0..LETTERBOX_SAMPLES {
+        let at = (duration - LETTERBOX_SAMPLE_SECS).max(0.0) * (i as f64 + 0.5) / LETTERBOX_SAMPLES as f64;
+        let output = Command::new("ffmpeg")
+            .args(["-hide_banner", "-loglevel", "info", "-ss", &format!("{:.3}", at)])
+            .args(["-t", &format!("{:.3}", LETTERBOX_SAMPLE_SECS), "-i"])
+            .arg(path)
+            .args([
+                "-an",
+                "-sn",
+                "-vf",
+                &format!("cropdetect=limit={}:round=2:reset=0", black_level.min(255)),
+                "-f",
+                "null",
+                "-",
+            ])
+            .output()
+            .await
+            .context("Failed to start ffmpeg cropdetect")?;
+        if !output.status.success() {
+            return Err(anyhow::anyhow!("ffmpeg cropdetect failed for {}", path.display()));
+        }
+
+        // The last `crop=w:h:x:y` of a sample covers every frame it saw (reset=0).
+        let log = String::from_utf8_lossy(&output.stderr);
+        let Some(rect) = log
+            .lines()
+            .rev()
+            .find_map(|line| CropRect::parse(line.split("crop=").nth(1)?.trim()))
+        else {
+            continue;
+        };
+        let (left, top, right, bottom) = (rect.x, rect.y, rect.x + rect.w, rect.y + rect.h);
+        found = Some(match found {
+            Some((l, t, r, b)) => (l.min(left), t.min(top), r.max(right), b.max(bottom)),
+            None => (left, top, right, bottom),
+        });
+    }
+
+    let Some((left, top, right, bottom)) = found else {
+        return Ok(None);
+    };
+    let (right, bottom) = (right.min(w), bottom.min(h));
+    let area = CropRect {
+        w: (right - left) & !1,
+        h: (bottom - top) & !1,
+        x: left,
+        y: top,
+    };
+    let trimmed_w = (w - area.w) as f64 / w.max(1) as f64;
+    let trimmed_h = (h - area.h) as f64 / h.max(1) as f64;
+    if area.w <= 0 || area.h <= 0 || (trimmed_w < LETTERBOX_MIN_BAR_FRACTION && trimmed_h < LETTERBOX_MIN_BAR_FRACTION) {
+        return Ok(None);
+    }
+    Ok(Some(area))
+}
+
+/// Decodes `path` at `fps` into `width`x`height` 8-bit grayscale frames (of the `area` part
+/// of the picture, when given) and hands each one to `on_frame`. Returns the number of frames read.
+pub async fn ffmpeg_gray_frames(
+    path: &Path,
+    area: Option<&CropRect>,
+    fps: f64,
+    width: usize,
+    height: usize,
+    mut on_frame: impl FnMut(&[u8]),
+) -> Result<usize> {
+    let mut child = Command::new("ffmpeg")
+        .args(["-hide_banner", "-loglevel", "error", "-i"])
+        .arg(path)
+        .args([
+            "-an",
+            "-sn",
+            "-vf",
+            &format!("{}fps={:.3},scale={}:{},format=gray", crop_prefix(area), fps, width, height),
+            "-f",
+            "rawvideo",
+            "-",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to start ffmpeg frame decode")?;
+    let mut stdout = child.stdout.take().context("ffmpeg stdout unavailable")?;
+
+    let mut frame = vec![0u8; width * height];
+    let mut frames = 0;
+    loop {
+        match stdout.read_exact(&mut frame).await {
+            Ok(_) => {
+                on_frame(&frame);
+                frames += 1;
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(err) => return Err(err).context("Failed to read decoded frames"),
+        }
+    }
+
+    let status = child.wait().await.context("ffmpeg frame decode failed")?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("ffmpeg frame decode failed for {}", path.display()));
+    }
+    Ok(frames)
+}
+
+/// Times (seconds into `path`) of shot cuts between `from` and `from + duration`: frames whose
+/// scene-change score is above `threshold`.
+pub async fn ffmpeg_scene_cuts(path: &Path, from: f64, duration: f64, threshold: f64) -> Result<Vec<f64>> {
+    let from = from.max(0.0);
+    let output = Command::new("ffmpeg")
+        .args(["-hide_banner", "-loglevel", "error", "-ss", &format!("{:.3}", from)])
+        .args(["-t", &format!("{:.3}", duration), "-i"])
+        .arg(path)
+        .args([
+            "-an",
+            "-sn",
+            "-vf",
+            &format!("select='gt(scene,{})',metadata=print:file=-", threshold),
+            "-f",
+            "null",
+            "-",
+        ])
+        .output()
+        .await
+        .context("Failed to start ffmpeg scene detection")?;
+    if !output.status.success() {
+        let err = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!("ffmpeg scene detection failed: {}", err.trim()));
+    }
+
+    // Lines look like `frame:0    pts:52052   pts_time:2.16883`, on a clock starting at `from`.
+    let cuts = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split("pts_time:").nth(1))
+        .filter_map(|rest| rest.split_whitespace().next()?.parse::<f64>().ok())
+        .map(|t| from + t)
+        .collect();
+    Ok(cuts)
+}
+
+/// One subtitle stream as reported by ffprobe.
+#[derive(Debug, Clone)]
+pub struct SubtitleStreamInfo {
+    /// Absolute stream index inside the container (usable as `-map 0:<index>`).
+    pub index: i64,
+    pub codec: String,
+    pub language: String,
+    pub title: String,
+    pub forced: bool,
+    pub hearing_impaired: bool,
+}
+
+impl SubtitleStreamInfo {
+    /// Image-based formats that would need OCR to become SRT.
+    pub fn is_bitmap(&self) -> bool {
+        matches!(
+            self.codec.as_str(),
+            "hdmv_pgs_subtitle" | "dvd_subtitle" | "dvb_subtitle" | "xsub"
+        )
+    }
+}
+
+pub async fn ffprobe_subtitle_streams(path: &Path) -> Result<Vec<SubtitleStreamInfo>> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "s",
+            "-show_entries",
+            "stream=index,codec_name:stream_tags=language,title:stream_disposition=forced,hearing_impaired",
+            "-of",
+            "json",
+        ])
+        .arg(path)
+        .output()
+        .await
+        .context("ffprobe subtitle streams failed")?;
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("ffprobe failed"));
+    }
+
+    let root: serde_json::Value =
+        serde_json::from_slice(&output.stdout).context("ffprobe returned invalid JSON")?;
+    let streams = root
+        .get("streams")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    let mut out = Vec::new();
+    for st in streams {
+        let tag = |key: &str| {
+            st.get("tags")
+                .and_then(|t| t.get(key))
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string()
+        };
+        let disposition = |key: &str| {
+            st.get("disposition")
+                .and_then(|d| d.get(key))
+                .and_then(|v| v.as_i64())
+                .unwrap_or(0)
+                != 0
+        };
+        out.push(SubtitleStreamInfo {
+            index: st.get("index").and_then(|v| v.as_i64()).unwrap_or(-1),
+            codec: st
+                .get("codec_name")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string(),
+            language: tag("language"),
+            title: tag("title"),
+            forced: disposition("forced"),
+            hearing_impaired: disposition("hearing_impaired"),
+        });
+    }
+    Ok(out)
+}
+
+/// Converts one subtitle stream (`stream_spec`, e.g. `0:s:0`) of `input` to SRT.
+/// Returns false when the stream is missing or cannot be converted to text.
+pub async fn ffmpeg_extract_subtitle(input: &Path, stream_spec: &str, out_srt: &Path) -> Result<bool> {
+    let output = Command::new("ffmpeg")
+        .args(["-y", "-hide_banner", "-loglevel", "error", "-i"])
+        .arg(input)
+        .args(["-map", stream_spec, "-c:s", "srt"])
+        .arg(out_srt)
+        .output()
+        .await
+        .context("ffmpeg subtitle extraction failed")?;
+
+    if !output.status.success() {
+        let err = String::from_utf8_lossy(&output.stderr);
+        logw(format!("Subtitle extraction failed ({}): {}", stream_spec, err.trim()));
+        let _ = tokio::fs::remove_file(out_srt).await;
+        return Ok(false);
+    }
+
+    let size = tokio::fs::metadata(out_srt).await.map(|m| m.len()).unwrap_or(0);
+    Ok(size > 0)
+}
+
 pub async fn ffmpeg_make_adjusted_clip(
     input_mp4: &Path,
-    start_s: i32,
-    end_s: i32,
+    start_s: f64,
+    end_s: f64,
     narration_mp3: &Path,
     narration_dur: f64,
+    area: Option<&CropRect>,
     out_mp4: &Path,
 ) -> Result<bool> {
-    let orig_seg_dur = (end_s - start_s) as f64;
+    let orig_seg_dur = end_s - start_s;
     if orig_seg_dur <= 0.1 || narration_dur <= 0.1 {
         return Ok(false);
     }
@@ -115,40 +434,37 @@ pub async fn ffmpeg_make_adjusted_clip(
             desired_src_dur = 1.0;
         }
 
-        let center = (start_s as f64 + end_s as f64) / 2.0;
+        let center = (start_s + end_s) / 2.0;
         let half = desired_src_dur / 2.0;
         let mut ns = center - half;
         let mut ne = center + half;
 
-        if ns < start_s as f64 {
-            ns = start_s as f64;
+        if ns < start_s {
+            ns = start_s;
             ne = ns + desired_src_dur;
         }
-        if ne > end_s as f64 {
-            ne = end_s as f64;
+        if ne > end_s {
+            ne = end_s;
             ns = ne - desired_src_dur;
         }
-        if ns < start_s as f64 {
-            ns = start_s as f64;
+        if ns < start_s {
+            ns = start_s;
         }
-        if ne > end_s as f64 {
-            ne = end_s as f64;
+        if ne > end_s {
+            ne = end_s;
         }
 
-        use_start = ns.round() as i32;
-        use_end = ne.round() as i32;
-        if use_end <= use_start {
-            use_end = use_start + 1;
-        }
+        use_start = ns;
+        use_end = ne;
 
-        let new_seg_dur = (use_end - use_start) as f64;
+        let new_seg_dur = use_end - use_start;
         speed = new_seg_dur / narration_dur;
         if speed > MAX_VIDEO_SPEEDUP {
             speed = MAX_VIDEO_SPEEDUP;
         }
 
         logi(format!(
-            "Speed-cap applied: planned {}-{} ({:.2}s) vs narr {:.2}s => {:.2}x. Using {}-{} ({:.2}s) => {:.2}x.",
+            "Speed-cap applied: planned {:.3}-{:.3} ({:.2}s) vs narr {:.2}s => {:.2}x. Using {:.3}-{:.3} ({:.2}s) => {:.2}x.",
             start_s,
             end_s,
             orig_seg_dur,
@@ -156,17 +472,12 @@ pub async fn ffmpeg_make_adjusted_clip(
             orig_seg_dur / narration_dur,
             use_start,
             use_end,
-            (use_end - use_start) as f64,
+            use_end - use_start,
             speed
         ));
     }
 
-    if speed < 0.05 {
-        speed = 0.05;
-    }
-    if speed > 20.0 {
-        speed = 20.0;
-    }
+    speed = speed.clamp(0.05, 20.0);
 
     let args = vec![
         "ffmpeg".to_string(),
@@ -175,15 +486,15 @@ pub async fn ffmpeg_make_adjusted_clip(
         "-loglevel".to_string(),
         "error".to_string(),
         "-ss".to_string(),
-        use_start.to_string(),
+        format!("{:.3}", use_start),
         "-to".to_string(),
-        use_end.to_string(),
+        format!("{:.3}", use_end),
         "-i".to_string(),
         input_mp4.display().to_string(),
         "-i".to_string(),
         narration_mp3.display().to_string(),
         "-filter_complex".to_string(),
-        format!("[0:v]setpts=PTS/{:.10}[v]", speed),
+        format!("[0:v]{}setpts=PTS/{:.10}[v]", crop_prefix(area), speed),
         "-map".to_string(),
         "[v]".to_string(),
         "-map".to_string(),
@@ -269,6 +580,27 @@ pub async fn ffmpeg_trim_audio(
     Ok(out_m4a.exists())
 }
 
+pub async fn ffmpeg_wav_to_mp3(in_wav: &Path, out_mp3: &Path) -> Result<bool> {
+    let args = vec![
+        "ffmpeg".to_string(),
+        "-y".to_string(),
+        "-hide_banner".to_string(),
+        "-loglevel".to_string(),
+        "error".to_string(),
+        "-i".to_string(),
+        in_wav.display().to_string(),
+        "-ar".to_string(),
+        "44100".to_string(),
+        "-c:a".to_string(),
+        "libmp3lame".to_string(),
+        "-b:a".to_string(),
+        "128k".to_string(),
+        out_mp3.display().to_string(),
+    ];
+    run_cmd(&args).await?;
+    Ok(out_mp3.exists())
+}
+
 pub async fn ffmpeg_concat_audio(list_txt: &Path, out_m4a: &Path) -> Result<bool> {
     let args = vec![
         "ffmpeg".to_string(),
@@ -321,25 +653,183 @@ pub async fn ffmpeg_mix_bgm(video_in: &Path, bgm_in: &Path, video_out: &Path) ->
     Ok(video_out.exists())
 }
 
-pub async fn ffmpeg_make_vertical(in_mp4: &Path, out_mp4: &Path) -> Result<bool> {
-    let (_w, h) = match ffprobe_video_dimensions(in_mp4).await {
+/// Copies `video_in` with `srt_in` added as a soft `mov_text` subtitle track.
+pub async fn ffmpeg_mux_soft_subtitles(video_in: &Path, srt_in: &Path, language: Option<&str>, video_out: &Path) -> Result<bool> {
+    let mut args = vec![
+        "ffmpeg".to_string(),
+        "-y".to_string(),
+        "-hide_banner".to_string(),
+        "-loglevel".to_string(),
+        "error".to_string(),
+        "-i".to_string(),
+        video_in.display().to_string(),
+        "-i".to_string(),
+        srt_in.display().to_string(),
+        "-map".to_string(),
+        "0:v".to_string(),
+        "-map".to_string(),
+        "0:a?".to_string(),
+        "-map".to_string(),
+        "1:s".to_string(),
+        "-c:v".to_string(),
+        "copy".to_string(),
+        "-c:a".to_string(),
+        "copy".to_string(),
+        "-c:s".to_string(),
+        "mov_text".to_string(),
+    ];
+    if let Some(language) = language {
+        args.push("-metadata:s:s:0".to_string());
+        args.push(format!("language={}", language));
+    }
+    args.extend([
+        "-movflags".to_string(),
+        "+faststart".to_string(),
+        video_out.display().to_string(),
+    ]);
+
+    if let Err(err) = run_cmd(&args).await {
+        logw(format!("Subtitle mux failed: {}", err));
+        return Ok(false);
+    }
+    Ok(video_out.exists())
+}
+
+/// `crop=...,` to put in front of a filter chain, or nothing.
+fn crop_prefix(area: Option<&CropRect>) -> String {
+    area.map(|a| format!("{},", a.filter())).unwrap_or_default()
+}
+
+/// Escapes a path for use as a filter option value inside `-filter_complex`: once for the
+/// option parser, once more for the filtergraph parser.
+fn escape_filter_path(path: &Path) -> String {
+    let value = path.display().to_string().replace('\\', "/");
+    let escape = |text: &str, special: &[char]| {
+        let mut out = String::with_capacity(text.len());
+        for c in text.chars() {
+            if special.contains(&c) {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+        out
+    };
+    let option = escape(&value, &['\\', '\'', ':']);
+    escape(&option, &['\\', '\'', '[', ']', ',', ';'])
+}
+
+/// Share of the source width kept by the `pad` layout (the rest is cut, then the crop is letterboxed).
+pub const VERTICAL_CROP_FRACTION: f64 = 0.6;
+
+/// Share of a `w`x`h` frame's width the vertical render crops to: the whole width for `blur`,
+/// a 9:16 window for `crop`.
+pub fn vertical_crop_fraction(layout: VerticalLayout, w: i32, h: i32) -> f64 {
+    match layout {
+        VerticalLayout::Blur => 1.0,
+        VerticalLayout::Pad => VERTICAL_CROP_FRACTION,
+        VerticalLayout::Crop => (h as f64 * 9.0 / 16.0 / w.max(1) as f64).min(1.0),
+    }
+}
+
+/// Where the vertical crop window sits over time: `(seconds, window centre as a fraction of the
+/// width)` pairs, written as crop commands to `commands_file` for the render to follow.
+pub struct CropPath<'a> {
+    pub points: &'a [(f64, f64)],
+    pub commands_file: &'a Path,
+}
+
+/// An ASS script to burn into a render, with the folder holding the fonts it names.
+pub struct BurnIn<'a> {
+    pub ass: &'a Path,
+    pub fonts_dir: &'a Path,
+}
+
+/// `canvas_h` is the height of the 9:16 output; `None` takes the input's frame height. Pass the
+/// source's height when `in_mp4` was already cropped to its active area, so bars don't shrink it.
+pub async fn ffmpeg_make_vertical(
+    in_mp4: &Path,
+    out_mp4: &Path,
+    framing: &VerticalConfig,
+    area: Option<&CropRect>,
+    canvas_h: Option<i32>,
+    crop_path: Option<&CropPath<'_>>,
+    captions: Option<&BurnIn<'_>>,
+) -> Result<bool> {
+    let (frame_w, frame_h) = match ffprobe_video_dimensions(in_mp4).await {
         Ok(v) => v,
         Err(_) => return Ok(false),
     };
+    // Crop positions refer to the picture inside any black bars.
+    let (w, h) = area.map(|a| (a.w, a.h)).unwrap_or((frame_w, frame_h));
     let dur = match ffprobe_duration_seconds(in_mp4).await {
         Ok(v) => v,
         Err(_) => return Ok(false),
     };
 
-    let mut out_w = ((h as f64) * 9.0 / 16.0 + 0.5) as i32;
-    let mut out_h = h;
+    let canvas_h = canvas_h.filter(|h| *h > 0).unwrap_or(frame_h);
+    let mut out_w = ((canvas_h as f64) * 9.0 / 16.0 + 0.5) as i32;
+    let mut out_h = canvas_h;
     out_w &= !1;
     out_h &= !1;
 
-    let filter = format!(
-        "[0:v]crop=iw*0.6:ih:iw*0.2:0,scale={}:{}:force_original_aspect_ratio=decrease,pad={}:{}:(ow-iw)/2:(oh-ih)/2:black[v]",
-        out_w, out_h, out_w, out_h
-    );
+    let burn = match captions {
+        Some(c) => format!(
+            ",subtitles=filename={}:fontsdir={}",
+            escape_filter_path(c.ass),
+            escape_filter_path(c.fonts_dir)
+        ),
+        None => String::new(),
+    };
+
+    let crop_w = ((w as f64 * vertical_crop_fraction(framing.layout, w, h)) as i32).clamp(2, w) & !1;
+    let crop_x = |center: f64| ((center * w as f64 - crop_w as f64 / 2.0).round() as i32).clamp(0, w - crop_w);
+    let crop = match crop_path.filter(|p| !p.points.is_empty() && framing.layout != VerticalLayout::Blur) {
+        Some(path) => {
+            let mut commands = String::new();
+            for (t, center) in path.points {
+                commands.push_str(&format!("{:.3} crop@reframe x {};\n", t, crop_x(*center)));
+            }
+            tokio::fs::write(path.commands_file, commands)
+                .await
+                .with_context(|| format!("Failed to write crop commands: {}", path.commands_file.display()))?;
+            format!(
+                "sendcmd=f={},crop@reframe={}:ih:{}:0",
+                escape_filter_path(path.commands_file),
+                crop_w,
+                crop_x(path.points[0].1)
+            )
+        }
+        None => format!("crop={}:ih:{}:0", crop_w, crop_x(0.5)),
+    };
+
+    let source = format!("[0:v]{}", crop_prefix(area));
+    let filter = match framing.layout {
+        VerticalLayout::Pad => format!(
+            "{}{},scale={}:{}:force_original_aspect_ratio=decrease,pad={}:{}:(ow-iw)/2:(oh-ih)/2:black{}[v]",
+            source, crop, out_w, out_h, out_w, out_h, burn
+        ),
+        VerticalLayout::Crop => format!("{}{},scale={}:{},setsar=1{}[v]", source, crop, out_w, out_h, burn),
+        VerticalLayout::Blur => {
+            // The background is blurred at quarter size: far cheaper, and invisible once scaled back up.
+            let (bg_w, bg_h) = ((out_w / 4).max(2) & !1, (out_h / 4).max(2) & !1);
+            let gain = framing.background_brightness.clamp(0.0, 1.0);
+            format!(
+                "{source}split=2[bg][fg];\
+                 [bg]scale={bw}:{bh}:force_original_aspect_ratio=increase,crop={bw}:{bh},gblur=sigma={sigma},\
+                 colorchannelmixer=rr={g}:gg={g}:bb={g},scale={w}:{h},setsar=1[back];\
+                 [fg]scale={w}:-2,setsar=1[front];\
+                 [back][front]overlay=(W-w)/2:(H-h)/2{burn}[v]",
+                source = source,
+                bw = bg_w,
+                bh = bg_h,
+                sigma = framing.background_blur.max(0.0),
+                g = gain,
+                w = out_w,
+                h = out_h,
+                burn = burn
+            )
+        }
+    };
 
     let args = vec![
         "ffmpeg".to_string(),
@@ -381,3 +871,36 @@ pub async fn ffmpeg_make_vertical(in_mp4: &Path, out_mp4: &Path) -> Result<bool>
 
     Ok(out_mp4.exists())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crop_rect_parses_cropdetect_values() {
+        let line = "[Parsed_cropdetect_0 @ 0x55] x1:0 x2:1919 y1:140 y2:939 w:1920 h:800 x:0 y:140 pts:0 t:0.04 crop=1920:800:0:140";
+        let rect = CropRect::parse(line.split("crop=").nth(1).unwrap().trim()).unwrap();
+        assert_eq!((rect.w, rect.h, rect.x, rect.y), (1920, 800, 0, 140));
+        assert_eq!(rect.filter(), "crop=1920:800:0:140");
+        assert_eq!(crop_prefix(Some(&rect)), "crop=1920:800:0:140,");
+        assert_eq!(crop_prefix(None), "");
+    }
+
+    #[test]
+    fn crop_rect_rejects_unusable_values() {
+        assert!(CropRect::parse("-1920:800:0:140").is_none());
+        assert!(CropRect::parse("1920:800:-2:140").is_none());
+        assert!(CropRect::parse("1920:800:0:-140").is_none());
+        assert!(CropRect::parse("0:800:0:140").is_none());
+        assert!(CropRect::parse("1920:800:0").is_none());
+        assert!(CropRect::parse("1920:eight:0:140").is_none());
+    }
+
+    #[test]
+    fn vertical_crop_fraction_matches_the_layout() {
+        assert_eq!(vertical_crop_fraction(VerticalLayout::Blur, 1920, 1080), 1.0);
+        assert_eq!(vertical_crop_fraction(VerticalLayout::Pad, 1920, 1080), VERTICAL_CROP_FRACTION);
+        assert!((vertical_crop_fraction(VerticalLayout::Crop, 1920, 1080) - 0.31640625).abs() < 1e-9);
+        assert_eq!(vertical_crop_fraction(VerticalLayout::Crop, 400, 1080), 1.0);
+    }
+}
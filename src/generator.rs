@@ -1,22 +1,34 @@
-use crate::api::{elevenlabs, openai};
-use crate::config::Config;
-use crate::ffmpeg;
+use crate::captions::{self, WordTiming};
+use crate::clip_plan::{ClipPlanList, PlanProvenance, MAX_CLIP_DURATION, MIN_CLIP_DURATION};
+use crate::config::{
+    self, CaptionConfig, Config, CutSnapConfig, LetterboxConfig, LanguageConfig, NarrationSubtitleConfig, PlanChunking, PlannerConfig, StyleConfig, StylePreset,
+    VerticalConfig, VerticalLayout,
+};
+use crate::ffmpeg::{self, BurnIn, CropPath, CropRect};
+use crate::job::{self, ActiveArea, InputHasher, JobManifest};
+use crate::narration::{self, NarrationSynth};
+use crate::planner::{self, BeatRequest, ClipPlanner, PlanFeedback, PlanRequest, PlannerError};
+use crate::reframe::{self, CropPoint};
+use crate::report::{MovieReport, MovieStatus, PlanAttempt, RunReport};
+use crate::subtitle::{SubtitleAttempt, SubtitleChain, SubtitleQuery};
 use crate::{logi, logok, logw};
 use anyhow::{Context, Result};
 use rand::{Rng, SeedableRng};
-use regex::Regex;
+use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use tokio::fs;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use walkdir::WalkDir;
-use zip::ZipArchive;
+use tokio::sync::{mpsc, Semaphore};
+use tokio::task::JoinSet;
+
+/// Source containers picked up from `movies/` (compared case-insensitively).
+const MOVIE_EXTENSIONS: &[&str] = &["mp4", "mkv", "mov", "avi", "webm", "m4v"];
 
 const MIN_NUM_CLIPS: i32 = 20;
 const MAX_NUM_CLIPS: i32 = 30;
-const MIN_TOTAL_DURATION: i32 = (2.5 * 60.0) as i32;
-const MAX_TOTAL_DURATION: i32 = (4.5 * 60.0) as i32;
 
 fn now_seed() -> u64 {
     SystemTime::now()
@@ -49,15 +61,33 @@ async fn write_entire_file(path: &Path, data: &[u8]) -> Result<()> {
     Ok(())
 }
 
-fn timestamp_to_seconds(ts: &str) -> Option<i32> {
-    let parts: Vec<&str> = ts.split([':', ',']).collect();
-    if parts.len() != 4 {
+/// Bumped whenever the converted-subtitle format changes so cached conversions are redone.
+const CONVERTED_SUBS_FORMAT: &str = "seconds.millis.2";
+
+/// `HH:MM:SS,mmm` or `MM:SS.mmm` to fractional seconds. The fraction may have fewer than three
+/// digits (`,5` is half a second), and cue settings after the time are ignored.
+fn timestamp_to_seconds(ts: &str) -> Option<f64> {
+    let ts = ts.split_whitespace().next()?;
+    let (clock, fraction) = match ts.rfind([',', '.']) {
+        Some(i) => (&ts[..i], &ts[i + 1..]),
+        None => (ts, ""),
+    };
+    if !fraction.bytes().all(|b| b.is_ascii_digit()) {
         return None;
     }
-    let hh = parts[0].parse::<i32>().ok()?;
-    let mm = parts[1].parse::<i32>().ok()?;
-    let ss = parts[2].parse::<i32>().ok()?;
-    Some(hh * 3600 + mm * 60 + ss)
+    let digits = fraction.get(..3).unwrap_or(fraction);
+    let ms = format!("{:0<3}", digits).parse::<u32>().ok()?;
+
+    let fields = clock
+        .split(':')
+        .map(|f| f.parse::<u32>().ok())
+        .collect::<Option<Vec<u32>>>()?;
+    let (hh, mm, ss) = match fields[..] {
+        [hh, mm, ss] => (hh, mm, ss),
+        [mm, ss] => (0, mm, ss),
+        _ => return None,
+    };
+    Some((hh * 3600 + mm * 60 + ss) as f64 + ms as f64 / 1000.0)
 }
 
 async fn convert_srt_timestamps_to_seconds(input_srt: &Path, output_srt: &Path) -> Result<bool> {
@@ -72,7 +102,7 @@ async fn convert_srt_timestamps_to_seconds(input_srt: &Path, output_srt: &Path)
             let s1 = timestamp_to_seconds(parts[0]);
             let s2 = timestamp_to_seconds(parts[1]);
             if let (Some(s1), Some(s2)) = (s1, s2) {
-                line = format!("{} --> {}", s1, s2);
+                line = format!("{:.3} --> {:.3}", s1, s2);
             }
         }
 
@@ -83,28 +113,6 @@ async fn convert_srt_timestamps_to_seconds(input_srt: &Path, output_srt: &Path)
     Ok(true)
 }
 
-fn parse_movie_title_slug(movie_title: &str) -> String {
-    let mut out = String::new();
-    for ch in movie_title.chars() {
-        match ch {
-            '\'' | '(' | ')' => continue,
-            ' ' => out.push('-'),
-            _ => out.push(ch.to_ascii_lowercase()),
-        }
-    }
-
-    if out.ends_with("ii") {
-        out.push_str("-2");
-    }
-    if out.ends_with("iii") {
-        out.push_str("-3");
-    }
-    if out.ends_with("iv") {
-        out.push_str("-4");
-    }
-    out
-}
-
 fn to_lower_copy(s: &str) -> String {
     s.chars().flat_map(|c| c.to_lowercase()).collect()
 }
@@ -138,7 +146,7 @@ fn html_to_text_basic(html: &str) -> String {
             while j < bytes.len() && bytes[j].is_ascii_whitespace() {
                 j += 1;
             }
-            if j + 1 < bytes.len() && bytes[j].to_ascii_lowercase() == b'b' && bytes[j + 1].to_ascii_lowercase() == b'r' {
+            if j + 1 < bytes.len() && bytes[j].eq_ignore_ascii_case(&b'b') && bytes[j + 1].eq_ignore_ascii_case(&b'r') {
                 out.push('\n');
             }
             while i < bytes.len() && bytes[i] != b'>' {
@@ -197,129 +205,6 @@ async fn http_get_text(client: &reqwest::Client, url: &str) -> Result<(reqwest::
     Ok((status, text))
 }
 
-async fn download_subtitle_srt(client: &reqwest::Client, movie_title: &str, dest_srt_path: &Path) -> Result<bool> {
-    ensure_dir(Path::new("scripts")).await?;
-    ensure_dir(Path::new("scripts/srt_files")).await?;
-
-    let slug = parse_movie_title_slug(movie_title);
-    let list_url = format!("https://subf2m.co/subtitles/{}/english", slug);
-    let (code, page) = http_get_text(client, &list_url).await?;
-    if !code.is_success() || page.is_empty() {
-        if !page.is_empty() {
-            let snippet = page.chars().take(200).collect::<String>();
-            logw(format!("subf2m list HTTP {} for {} (body starts: {})", code.as_u16(), list_url, snippet));
-        }
-        return Ok(false);
-    }
-
-    let want_prefix = format!("/subtitles/{}/english/", slug);
-    let mut subpage_url = String::new();
-
-    let href_re = Regex::new(r#"href=["']([^"']+)["']"#).unwrap();
-    for cap in href_re.captures_iter(&page) {
-        let href = &cap[1];
-        if href.starts_with(&want_prefix) {
-            if href.contains("english-german") {
-                continue;
-            }
-            subpage_url = format!("https://subf2m.co{}", href);
-            break;
-        }
-    }
-
-    if subpage_url.is_empty() {
-        let mut tried_profiles = 0;
-        for cap in href_re.captures_iter(&page) {
-            let href = &cap[1];
-            if !href.starts_with("/u/") {
-                continue;
-            }
-            let profile_url = format!("https://subf2m.co{}", href);
-            let (pcode, prof) = http_get_text(client, &profile_url).await?;
-            if !pcode.is_success() || prof.is_empty() {
-                continue;
-            }
-            for cap2 in href_re.captures_iter(&prof) {
-                let phref = &cap2[1];
-                if phref.starts_with(&want_prefix) {
-                    subpage_url = format!("https://subf2m.co{}", phref);
-                    break;
-                }
-            }
-            if !subpage_url.is_empty() {
-                break;
-            }
-            tried_profiles += 1;
-            if tried_profiles >= 12 {
-                break;
-            }
-        }
-    }
-
-    if subpage_url.is_empty() {
-        logw(format!("subf2m: couldn't locate subtitle detail page for {} (slug={})", movie_title, slug));
-        return Ok(false);
-    }
-
-    let (scode, subpage) = http_get_text(client, &subpage_url).await?;
-    if !scode.is_success() || subpage.is_empty() {
-        logw(format!("subf2m: subtitle detail HTTP {} for {}", scode.as_u16(), subpage_url));
-        return Ok(false);
-    }
-
-    let mut download_url = String::new();
-    for cap in href_re.captures_iter(&subpage) {
-        let href = &cap[1];
-        if href.ends_with("download") {
-            download_url = format!("https://subf2m.co{}", href);
-            break;
-        }
-    }
-
-    if download_url.is_empty() {
-        logw(format!("subf2m: couldn't find download link on {}", subpage_url));
-        return Ok(false);
-    }
-
-    let tmpzip = PathBuf::from(format!("scripts/srt_files/{}_tmp.zip", movie_title));
-    let zip_bytes = client
-        .get(&download_url)
-        .header(
-            "User-Agent",
-            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.0 Safari/605.1.15",
-        )
-        .header("Cookie", "")
-        .timeout(std::time::Duration::from_secs(120))
-        .send()
-        .await?
-        .bytes()
-        .await?;
-
-    fs::write(&tmpzip, &zip_bytes).await?;
-
-    let file = std::fs::File::open(&tmpzip)?;
-    let mut archive = ZipArchive::new(file)?;
-    let mut extracted: Option<Vec<u8>> = None;
-    for i in 0..archive.len() {
-        let mut f = archive.by_index(i)?;
-        let name = f.name().to_ascii_lowercase();
-        if name.ends_with(".srt") {
-            let mut buf = Vec::new();
-            std::io::copy(&mut f, &mut buf)?;
-            extracted = Some(buf);
-            break;
-        }
-    }
-
-    let _ = fs::remove_file(&tmpzip).await;
-    if let Some(data) = extracted {
-        fs::write(dest_srt_path, data).await?;
-        return Ok(file_exists(dest_srt_path).await);
-    }
-
-    Ok(false)
-}
-
 fn strip_parens(input: &str) -> String {
     input.chars().filter(|c| *c != '(' && *c != ')').collect()
 }
@@ -462,54 +347,282 @@ async fn list_files_with_ext(dir: &Path, ext1: &str, ext2: &str) -> Result<Vec<P
     Ok(out)
 }
 
-async fn clear_directory_contents(dir_path: &Path) -> Result<bool> {
-    if !dir_exists(dir_path).await {
-        return Ok(true);
+/// Limits shared by every movie in a run: concurrent TTS API calls and concurrent ffmpeg renders.
+#[derive(Clone)]
+struct RenderLimits {
+    tts: Arc<Semaphore>,
+    ffmpeg: Arc<Semaphore>,
+}
+
+impl RenderLimits {
+    fn from_config(cfg: &Config) -> Self {
+        Self {
+            tts: Arc::new(Semaphore::new(cfg.concurrency.tts_requests.max(1))),
+            ffmpeg: Arc::new(Semaphore::new(cfg.concurrency.ffmpeg_processes.max(1))),
+        }
     }
+}
 
-    for entry in WalkDir::new(dir_path).min_depth(1).contents_first(true) {
-        let entry = entry?;
-        let path = entry.path();
-        if path.is_dir() {
-            fs::remove_dir(path).await.ok();
-        } else {
-            fs::remove_file(path).await.ok();
+/// A stage finished by a clip task, reported back so the owner of the manifest can record it.
+struct StageDone {
+    stage: String,
+    input_hash: String,
+    output: PathBuf,
+}
+
+struct ClipWork {
+    movie_title: String,
+    movie_path: PathBuf,
+    work_dir: PathBuf,
+    clip_index: usize,
+    total: usize,
+    start_s: f64,
+    end_s: f64,
+    /// Picture area inside the source's black bars, cropped out of the clip.
+    area: Option<CropRect>,
+    narration: String,
+    nar_mp3: PathBuf,
+    out_clip_name: String,
+    tts_stage: String,
+    tts_hash: String,
+    tts_cached: bool,
+    render_stage: String,
+    render_hash: String,
+    render_cached: bool,
+}
+
+/// A rendered clip with its narration's words, timed from the start of the clip.
+struct BuiltClip {
+    clip_index: usize,
+    out_clip_name: String,
+    duration: f64,
+    words: Vec<WordTiming>,
+    /// The backend reported no timings, so `words` were spread evenly over the narration.
+    words_estimated: bool,
+}
+
+/// TTS + re-timed render for one clip. `Ok(None)` means the clip was skipped (soft failure),
+/// matching the sequential loop this replaced.
+async fn build_clip(
+    work: ClipWork,
+    synth: Arc<dyn NarrationSynth>,
+    limits: RenderLimits,
+    done: mpsc::UnboundedSender<StageDone>,
+) -> Result<Option<BuiltClip>> {
+    let clip_index = work.clip_index;
+    let nar_mp3 = &work.nar_mp3;
+    let words_json = nar_mp3.with_extension("words.json");
+
+    let mut words: Vec<WordTiming> = if work.tts_cached {
+        logok(format!("Reusing TTS clip {}/{}: {}", clip_index, work.total, nar_mp3.display()));
+        captions::load_words(&words_json).await.unwrap_or_default()
+    } else {
+        let _permit = limits.tts.acquire().await.context("TTS limiter closed")?;
+        logi(format!("TTS clip {}/{} -> {}", clip_index, work.total, nar_mp3.display()));
+        let _ = fs::remove_file(&words_json).await;
+        let Some(timed) = synth.synthesize_timed(&work.narration, nar_mp3).await? else {
+            logw(format!("TTS failed clip {} for {}", clip_index, work.movie_title));
+            return Ok(None);
+        };
+        if !timed.is_empty() {
+            captions::save_words(&words_json, &timed).await?;
         }
+        let _ = done.send(StageDone {
+            stage: work.tts_stage.clone(),
+            input_hash: work.tts_hash.clone(),
+            output: nar_mp3.clone(),
+        });
+        timed
+    };
+
+    let nar_dur = match ffmpeg::ffprobe_duration_seconds(nar_mp3).await {
+        Ok(v) => v,
+        Err(_) => {
+            logw(format!("Bad narration duration for clip {}", clip_index));
+            return Ok(None);
+        }
+    };
+
+    let words_estimated = words.is_empty();
+    if words_estimated {
+        words = captions::estimate_word_timings(&work.narration, nar_dur);
     }
 
-    Ok(true)
+    let out_clip = work.work_dir.join(&work.out_clip_name);
+    if work.render_cached {
+        logok(format!("Reusing clip {}: {}", clip_index, out_clip.display()));
+    } else {
+        let _permit = limits.ffmpeg.acquire().await.context("ffmpeg limiter closed")?;
+        logi(format!(
+            "Building clip {}: {:.3} -> {:.3} sec (narr={:.2}s) => {}",
+            clip_index, work.start_s, work.end_s, nar_dur, out_clip.display()
+        ));
+        if !ffmpeg::ffmpeg_make_adjusted_clip(
+            &work.movie_path,
+            work.start_s,
+            work.end_s,
+            nar_mp3,
+            nar_dur,
+            work.area.as_ref(),
+            &out_clip,
+        )
+        .await? {
+            logw(format!("Failed to build adjusted clip {}", clip_index));
+            return Ok(None);
+        }
+        let _ = done.send(StageDone {
+            stage: work.render_stage.clone(),
+            input_hash: work.render_hash.clone(),
+            output: out_clip.clone(),
+        });
+        logok(format!("Built clip {} OK: {}", clip_index, out_clip.display()));
+    }
+
+    let duration = ffmpeg::ffprobe_duration_seconds(&out_clip).await.unwrap_or(nar_dur);
+    Ok(Some(BuiltClip {
+        clip_index,
+        out_clip_name: work.out_clip_name,
+        duration,
+        words,
+        words_estimated,
+    }))
 }
 
-async fn process_movie(cfg: &Config, client: &reqwest::Client, movie_path: &Path, movie_title: &str, num_clips: i32) -> Result<bool> {
-    ensure_dir(Path::new("clips")).await?;
-    ensure_dir(Path::new("clips/audio")).await?;
+/// Services shared by every movie in a run.
+struct Pipeline {
+    seed: u64,
+    /// Feedback rounds, subtitle chunking and opening-line settings.
+    planning: PlannerConfig,
+    /// Planning prompt template text; empty means the built-in prompt.
+    plan_template: String,
+    /// File `plan_template` was read from; `None` for the built-in prompt.
+    plan_template_file: Option<PathBuf>,
+    styles: StyleConfig,
+    /// Preset picked for the whole run, ahead of per-movie choices.
+    style_override: Option<String>,
+    /// Language profiles, with `targets` already overridden by `--lang`.
+    languages: LanguageConfig,
+    client: reqwest::Client,
+    planner: Box<dyn ClipPlanner>,
+    synth: Arc<dyn NarrationSynth>,
+    subtitles: SubtitleChain,
+    limits: RenderLimits,
+    captions: CaptionConfig,
+    narration_subtitles: NarrationSubtitleConfig,
+    vertical: VerticalConfig,
+    cut_snap: CutSnapConfig,
+    letterbox: LetterboxConfig,
+}
+
+/// One movie to push through the pipeline.
+struct MovieTask {
+    path: PathBuf,
+    title: String,
+    num_clips: i32,
+    /// `num_clips` was asked for explicitly (`--clips`), so it replaces the count a previous
+    /// run recorded in the job manifest.
+    fixed_clips: bool,
+    /// Render this plan instead of asking the planner.
+    plan: Option<ClipPlanList>,
+    /// The `inputs` block saved with `plan`, kept when the snapped plan is written to the work dir.
+    plan_inputs: Option<PlanProvenance>,
+    /// Move the source into `movies_retired/` once done (batch runs only).
+    retire: bool,
+    /// Stop after planning and copy the plan here for review instead of rendering.
+    plan_out: Option<PathBuf>,
+    /// Narration language code; empty for the default English recap.
+    language: String,
+}
+
+impl MovieTask {
+    /// Name of this recap's work dir, plan and outputs.
+    fn output_name(&self) -> String {
+        localized_name(&self.title, &self.language)
+    }
+}
+
+/// Subtitles (and optional script) the planner works from.
+struct PlanInputs {
+    movie_file: PathBuf,
+    subs_file: PathBuf,
+    subs_seconds: String,
+    subs_hash: String,
+    script_file: PathBuf,
+    script: Option<String>,
+}
+
+async fn ensure_movie_dirs(work_dir: &Path) -> Result<()> {
+    ensure_dir(work_dir).await?;
+    ensure_dir(&work_dir.join("audio")).await?;
     ensure_dir(Path::new("output")).await?;
     ensure_dir(Path::new("tiktok_output")).await?;
     ensure_dir(Path::new("scripts")).await?;
     ensure_dir(Path::new("scripts/srt_files")).await?;
     ensure_dir(Path::new("movies_retired")).await?;
+    Ok(())
+}
+
+async fn open_job(movie_title: &str) -> JobManifest {
+    let job = JobManifest::load_or_new(&JobManifest::manifest_path(movie_title), movie_title).await;
+    if job.is_resumed() {
+        logi(format!("Resuming job for {} ({} stages recorded)", movie_title, job.stages.len()));
+    }
+    job
+}
 
+/// Makes sure `scripts/srt_files/{title}.srt` exists, running the provider chain if needed and
+/// adding each provider tried to `attempts`.
+async fn ensure_subtitles(
+    pipe: &Pipeline,
+    movie_path: &Path,
+    movie_title: &str,
+    attempts: &mut Vec<SubtitleAttempt>,
+) -> Result<Option<PathBuf>> {
+    let subtitles = &pipe.subtitles;
     let srt_in = PathBuf::from(format!("scripts/srt_files/{}.srt", movie_title));
-    let srt_mod = PathBuf::from(format!("scripts/srt_files/{}_modified.srt", movie_title));
-    let script_txt = PathBuf::from(format!("scripts/srt_files/{}_summary.txt", movie_title));
 
     if !file_exists(&srt_in).await {
-        logi(format!("No SRT found for {}; attempting download...", movie_title));
-        if !download_subtitle_srt(client, movie_title, &srt_in).await? {
-            logw(format!("Subtitle download failed for {}. Place your SRT at: {}", movie_title, srt_in.display()));
-            return Ok(false);
+        logi(format!("No SRT found for {}; trying providers: {}", movie_title, subtitles.describe()));
+        let query = SubtitleQuery {
+            movie_title,
+            movie_path,
+        };
+        let (found, tried) = subtitles.fetch(&query, &srt_in).await?;
+        attempts.extend(tried);
+        if !found {
+            logw(format!("Subtitle acquisition failed for {}. Place your SRT at: {}", movie_title, srt_in.display()));
+            return Ok(None);
         }
-        logok(format!("Downloaded SRT: {}", srt_in.display()));
+        logok(format!("Fetched SRT: {}", srt_in.display()));
     } else {
         logok(format!("Found SRT: {}", srt_in.display()));
     }
+    Ok(Some(srt_in))
+}
+
+async fn load_plan_inputs(
+    pipe: &Pipeline,
+    job: &mut JobManifest,
+    movie_path: &Path,
+    movie_title: &str,
+    report: &mut MovieReport,
+) -> Result<Option<PlanInputs>> {
+    let client = &pipe.client;
+    let Some(srt_in) = ensure_subtitles(pipe, movie_path, movie_title, &mut report.subtitle_attempts).await? else {
+        return Ok(None);
+    };
+    let srt_mod = PathBuf::from(format!("scripts/srt_files/{}_modified.srt", movie_title));
+    let script_txt = PathBuf::from(format!("scripts/srt_files/{}_summary.txt", movie_title));
 
-    if !file_exists(&srt_mod).await {
+    let subs_hash = job::hash_file(&srt_in).await?;
+    let convert_hash = InputHasher::new().str(&subs_hash).str(CONVERTED_SUBS_FORMAT).finish();
+    if !job.is_complete(job::STAGE_SUBTITLES, &convert_hash).await {
         logi(format!("Converting SRT timestamps -> seconds: {} -> {}", srt_in.display(), srt_mod.display()));
         if !convert_srt_timestamps_to_seconds(&srt_in, &srt_mod).await? {
             logw(format!("Failed to convert SRT for {}", movie_title));
-            return Ok(false);
+            return Ok(None);
         }
+        job.mark_complete(job::STAGE_SUBTITLES, &convert_hash, &[&srt_mod]).await?;
         logok(format!("Converted subtitles (seconds): {}", srt_mod.display()));
     } else {
         logok(format!("Using cached converted subtitles: {}", srt_mod.display()));
@@ -549,202 +662,1196 @@ async fn process_movie(cfg: &Config, client: &reqwest::Client, movie_path: &Path
             }
         }
     } else {
-        logi("No IMSDb script available; using subtitles only.".to_string());
+        logi("No IMSDb script available; using subtitles only.");
     }
 
-    logi(format!("Requesting OpenAI clip plan ({} clips target)...", num_clips));
-    let (mut plan, retry_no_script) = openai::openai_make_plan(
-        client,
-        cfg,
+    Ok(Some(PlanInputs {
+        movie_file: movie_path.to_path_buf(),
+        subs_file: srt_in,
+        subs_seconds,
+        subs_hash,
+        script_file: script_txt,
+        script: imsdb_script,
+    }))
+}
+
+async fn probe_movie_duration(movie_path: &Path) -> Option<f64> {
+    match ffmpeg::ffprobe_duration_seconds(movie_path).await {
+        Ok(d) => Some(d),
+        Err(err) => {
+            logw(format!("Could not probe duration of {} ({}); skipping range checks.", movie_path.display(), err));
+            None
+        }
+    }
+}
+
+/// One planner call, repeated without the IMSDb script when the backend suggests the context
+/// was the problem. Returns the plan and whether the script had to be dropped.
+async fn request_plan(planner: &dyn ClipPlanner, req: &PlanRequest<'_>) -> Result<(ClipPlanList, bool)> {
+    let (plan, retry_no_script) = planner.make_plan(req).await?;
+    if !plan.items.is_empty() || !retry_no_script || req.script_text.is_empty() {
+        return Ok((plan, false));
+    }
+
+    logw(format!("Planner request failed with IMSDb context; retrying without IMSDb script for {}", req.movie_title));
+    let req = PlanRequest { script_text: "", ..*req };
+    let (plan, _) = planner.make_plan(&req).await?;
+    Ok((plan, true))
+}
+
+/// Condenses long subtitles window by window into a timestamped beat list (cached in
+/// `scripts/srt_files/{title}_beats.txt`). `Ok(None)` means a window failed and the caller
+/// should fall back to raw subtitles.
+///
+/// The beats are shared by every language of a movie and outlive its work dir, so the hash they
+/// were built from is kept next to them (`{title}_beats.hash`) rather than in a job manifest.
+async fn condense_subtitles(pipe: &Pipeline, movie_title: &str, inputs: &PlanInputs) -> Result<Option<(PathBuf, String)>> {
+    let planner = pipe.planner.as_ref();
+    let window_chars = pipe.planning.beat_window_chars;
+    let beats_txt = PathBuf::from(format!("scripts/srt_files/{}_beats.txt", movie_title));
+    let beats_stamp = beats_txt.with_extension("hash");
+    let beats_hash = InputHasher::new()
+        .str(&inputs.subs_hash)
+        .num(window_chars)
+        .str(&planner.describe())
+        .finish();
+
+    let stamp = read_entire_file(&beats_stamp).await.unwrap_or_default();
+    if stamp.trim() == beats_hash {
+        if let Ok(text) = read_entire_file(&beats_txt).await {
+            logok(format!("Reusing condensed beats: {} ({} bytes)", beats_txt.display(), text.len()));
+            return Ok(Some((beats_txt, text)));
+        }
+    }
+
+    let windows = planner::split_subtitle_windows(&inputs.subs_seconds, window_chars);
+    logi(format!(
+        "Condensing {} bytes of subtitles for {} into beats ({} windows)...",
+        inputs.subs_seconds.len(),
         movie_title,
-        &subs_seconds,
-        imsdb_script.as_deref().unwrap_or(""),
-        num_clips,
-    )
-    .await?;
+        windows.len()
+    ));
 
-    if plan.items.is_empty() && retry_no_script && imsdb_script.is_some() {
-        logw(format!("OpenAI request failed with IMSDb context; retrying without IMSDb script for {}", movie_title));
-        let (retry_plan, _) = openai::openai_make_plan(
-            client,
-            cfg,
+    let mut beats = Vec::new();
+    for (index, window_text) in windows.iter().enumerate() {
+        let req = BeatRequest {
             movie_title,
-            &subs_seconds,
-            "",
-            num_clips,
-        )
-        .await?;
-        plan = retry_plan;
+            window_text,
+            index,
+            total: windows.len(),
+        };
+        let window_beats = match planner.summarize_window(&req).await {
+            Ok(found) => found,
+            Err(err) => {
+                logw(format!("Beat window {}/{} failed for {}: {:#}", index + 1, windows.len(), movie_title, err));
+                Vec::new()
+            }
+        };
+        if window_beats.is_empty() {
+            logw(format!("No beats for window {}/{} of {}", index + 1, windows.len(), movie_title));
+            return Ok(None);
+        }
+        logi(format!("Beat window {}/{}: {} beats", index + 1, windows.len(), window_beats.len()));
+        beats.extend(window_beats);
     }
+    beats.sort_by(|a, b| a.start.total_cmp(&b.start));
 
-    if plan.items.is_empty() {
-        logw(format!("No plan returned for {}", movie_title));
-        return Ok(false);
+    let text = planner::format_beats(&beats);
+    write_entire_file(&beats_txt, text.as_bytes()).await?;
+    write_entire_file(&beats_stamp, beats_hash.as_bytes()).await?;
+    logok(format!("Condensed subtitles into {} beats: {} ({} bytes)", beats.len(), beats_txt.display(), text.len()));
+    Ok(Some((beats_txt, text)))
+}
+
+/// Runs the plan validator against the probed movie length and logs what it changed.
+/// Returns false when no usable clips are left.
+fn repair_plan(plan: &mut ClipPlanList, movie_duration: Option<f64>, movie_title: &str) -> bool {
+    let before = plan.items.len();
+    let validation = plan.validate_and_repair(movie_duration);
+    if validation.is_clean() {
+        logok(format!("Clip plan for {} passed validation ({} clips, {:.1}s)", movie_title, before, plan.total_duration()));
+        return true;
+    }
+    for fix in &validation.fixes {
+        logi(format!("Plan repair ({}): {}", movie_title, fix));
     }
+    for problem in &validation.problems {
+        logw(format!("Plan problem ({}): {}", movie_title, problem));
+    }
+    logi(format!(
+        "Clip plan for {}: {} -> {} clips, {:.1}s total after repair",
+        movie_title,
+        before,
+        plan.items.len(),
+        plan.total_duration()
+    ));
+    !plan.items.is_empty()
+}
 
-    let concat_list_path = PathBuf::from(format!("clips/{}_concat_list.txt", movie_title));
-    let mut listf = fs::File::create(&concat_list_path).await?;
+/// How far inside the new shot a snapped boundary lands, so rounding never pulls in a frame of
+/// the neighbouring shot (less than one frame at 24 fps).
+const CUT_GUARD_SECS: f64 = 0.02;
+
+/// Nearest shot cut to `at` within the configured tolerance, or `None` when there is none or
+/// detection failed.
+async fn nearest_cut(pipe: &Pipeline, movie_path: &Path, at: f64) -> Option<f64> {
+    let snap = &pipe.cut_snap;
+    // Start a little early: the first decoded frame has no predecessor to score against.
+    let lead = 0.5;
+    let from = (at - snap.tolerance_secs - lead).max(0.0);
+    let duration = at + snap.tolerance_secs - from;
+    let cuts = {
+        let _permit = pipe.limits.ffmpeg.acquire().await.ok()?;
+        ffmpeg::ffmpeg_scene_cuts(movie_path, from, duration, snap.threshold).await
+    };
+    match cuts {
+        Ok(cuts) => cuts
+            .into_iter()
+            .filter(|cut| (cut - at).abs() <= snap.tolerance_secs)
+            .min_by(|a, b| (a - at).abs().total_cmp(&(b - at).abs())),
+        Err(err) => {
+            logw(format!("Shot cut detection failed near {:.3}s: {}", at, err));
+            None
+        }
+    }
+}
 
-    let mut made = 0usize;
-    for (idx, clip) in plan.items.iter().enumerate() {
-        let start_s = clip.start;
-        let end_s = clip.end;
-        let clip_index = idx + 1;
-        if start_s <= 0 {
-            logw(format!("Skipping clip {} (start<=0)", clip_index));
-            continue;
+/// Moves each clip's start and end onto the nearest shot cut within `cut_snap.tolerance_secs`,
+/// keeping the original times in `planned_start`/`planned_end`. Moves that would make a clip
+/// too short or long, or overlap a neighbour, are skipped.
+async fn snap_plan_to_cuts(pipe: &Pipeline, movie_path: &Path, plan: &mut ClipPlanList, movie_title: &str) {
+    if !pipe.cut_snap.enabled || pipe.cut_snap.tolerance_secs <= 0.0 {
+        return;
+    }
+    logi(format!("Looking for shot cuts near {} clip boundaries of {}", plan.items.len() * 2, movie_title));
+    let fits = |start: f64, end: f64| {
+        let dur = end - start;
+        start > 0.0 && (MIN_CLIP_DURATION - 1e-6..=MAX_CLIP_DURATION + 1e-6).contains(&dur)
+    };
+
+    let mut moved = 0;
+    for i in 0..plan.items.len() {
+        let prev_end = if i > 0 { plan.items[i - 1].end } else { 0.0 };
+        let next_start = plan.items.get(i + 1).map(|c| c.start).unwrap_or(f64::INFINITY);
+
+        let planned = plan.items[i].start;
+        if let Some(cut) = nearest_cut(pipe, movie_path, planned).await {
+            let start = cut + CUT_GUARD_SECS;
+            let clip = &mut plan.items[i];
+            if (start - clip.start).abs() > 1e-3 && start >= prev_end && fits(start, clip.end) {
+                logi(format!("Clip {}: start {:.3} -> {:.3} (shot cut)", i + 1, clip.start, start));
+                clip.planned_start.get_or_insert(clip.start);
+                clip.start = start;
+                moved += 1;
+            }
         }
-        if end_s <= start_s {
-            logw(format!("Skipping clip {} (end<=start)", clip_index));
-            continue;
+
+        let planned = plan.items[i].end;
+        if let Some(cut) = nearest_cut(pipe, movie_path, planned).await {
+            let end = cut - CUT_GUARD_SECS;
+            let clip = &mut plan.items[i];
+            if (end - clip.end).abs() > 1e-3 && end <= next_start && fits(clip.start, end) {
+                logi(format!("Clip {}: end {:.3} -> {:.3} (shot cut)", i + 1, clip.end, end));
+                clip.planned_end.get_or_insert(clip.end);
+                clip.end = end;
+                moved += 1;
+            }
         }
+    }
+    if moved > 0 {
+        logok(format!("Snapped {} clip boundaries of {} to shot cuts", moved, movie_title));
+    }
+}
 
-        let nar_mp3 = PathBuf::from(format!("clips/audio/{}_audio_{}.mp3", movie_title, clip_index));
-        logi(format!("TTS clip {}/{} -> {}", clip_index, plan.items.len(), nar_mp3.display()));
-        if !elevenlabs::elevenlabs_tts_to_mp3(client, cfg, &clip.narration, &nar_mp3).await? {
-            logw(format!("TTS failed clip {} for {}", clip_index, movie_title));
-            continue;
+/// Cut-snapping settings, hashed into the plan stage so changing them re-snaps a cached plan.
+fn cut_snap_key(snap: &CutSnapConfig) -> String {
+    format!("{}:{}:{}", snap.enabled, snap.tolerance_secs, snap.threshold)
+}
+
+/// Returns the saved plan when its inputs are unchanged, otherwise asks the planner.
+async fn obtain_plan(
+    pipe: &Pipeline,
+    job: &mut JobManifest,
+    task: &MovieTask,
+    style: &MovieStyle,
+    inputs: &PlanInputs,
+    num_clips: i32,
+    attempts: &mut Vec<PlanAttempt>,
+) -> Result<Option<(ClipPlanList, String)>> {
+    let planner = pipe.planner.as_ref();
+    let movie_title = task.title.as_str();
+    let output_name = task.output_name();
+    let subs_hash = &inputs.subs_hash;
+    let subs_seconds = &inputs.subs_seconds;
+    let imsdb_script = &inputs.script;
+
+    let use_beats = match pipe.planning.chunking {
+        PlanChunking::Always => true,
+        PlanChunking::Never => false,
+        PlanChunking::Auto => subs_seconds.len() > planner::MAX_SUB_CHARS,
+    };
+
+    // A custom template that never asks for the opening line cannot be held to it.
+    let opening_line = if pipe.plan_template.is_empty() || pipe.plan_template.contains("{{opening_line}}") {
+        style.language.opening_line.as_str()
+    } else {
+        ""
+    };
+
+    let plan_json = work_plan_path(&JobManifest::work_dir(&output_name), &output_name);
+    let plan_hash = InputHasher::new()
+        .str(subs_hash)
+        .str(imsdb_script.as_deref().unwrap_or(""))
+        .num(num_clips)
+        .str(&planner.describe())
+        .num(if use_beats { pipe.planning.beat_window_chars } else { 0 })
+        .str(&pipe.plan_template)
+        .str(opening_line)
+        .str(&style.language.name)
+        .str(&style.name)
+        .str(&style.preset.instructions)
+        .num(style.preset.min_words)
+        .num(style.preset.max_words)
+        .str(&cut_snap_key(&pipe.cut_snap))
+        .finish();
+
+    let mut plan = ClipPlanList::default();
+    if job.is_complete(job::STAGE_PLAN, &plan_hash).await {
+        match read_entire_file(&plan_json).await.and_then(|text| ClipPlanList::from_json(&text)) {
+            Ok(saved) => {
+                logok(format!("Reusing saved clip plan: {} ({} clips)", plan_json.display(), saved.items.len()));
+                plan = saved;
+            }
+            Err(err) => logw(format!("Saved clip plan unreadable ({}); requesting a new one.", err)),
         }
+    }
 
-        let nar_dur = match ffmpeg::ffprobe_duration_seconds(&nar_mp3).await {
-            Ok(v) => v,
-            Err(_) => {
-                logw(format!("Bad narration duration for clip {}", clip_index));
-                continue;
+    if plan.items.is_empty() {
+        let movie_duration = probe_movie_duration(&inputs.movie_file).await;
+        let beats = if use_beats {
+            let condensed = condense_subtitles(pipe, movie_title, inputs).await?;
+            if condensed.is_none() {
+                logw(format!("Falling back to raw (possibly truncated) subtitles for {}", movie_title));
             }
+            condensed
+        } else {
+            None
+        };
+        let mut base = PlanRequest {
+            movie_title,
+            subs_seconds_text: beats.as_ref().map(|(_, text)| text.as_str()).unwrap_or(subs_seconds),
+            script_text: imsdb_script.as_deref().unwrap_or(""),
+            num_clips,
+            feedback: None,
+            beats: beats.is_some(),
+            template: &pipe.plan_template,
+            opening_line,
+            style_instructions: &style.preset.instructions,
+            min_words: style.preset.min_words,
+            max_words: style.preset.max_words,
+            language: &style.language.name,
         };
+        let max_attempts = 1 + pipe.planning.feedback_rounds;
+        let mut feedback: Option<PlanFeedback> = None;
+        let mut best: Option<(ClipPlanList, usize)> = None;
+
+        for attempt in 1..=max_attempts {
+            logi(format!(
+                "Requesting clip plan from {} ({} clips target, attempt {}/{})...",
+                planner.describe(),
+                num_clips,
+                attempt,
+                max_attempts
+            ));
+            let req = PlanRequest {
+                feedback: feedback.as_ref(),
+                ..base
+            };
+            let (candidate, dropped_script) = match request_plan(planner, &req).await {
+                Ok(found) => found,
+                Err(err) => {
+                    let err = err.downcast::<PlannerError>()?;
+                    logw(format!("Plan attempt {} ({}): {}", attempt, movie_title, err));
+                    attempts.push(PlanAttempt {
+                        attempt,
+                        clips: 0,
+                        violations: vec![err.to_string()],
+                    });
+                    feedback = err.output().map(|output| PlanFeedback {
+                        previous_json: output.to_string(),
+                        violations: vec![err.to_string()],
+                    });
+                    continue;
+                }
+            };
+            if dropped_script {
+                base.script_text = "";
+            }
+            if candidate.items.is_empty() {
+                logw(format!("Planner attempt {} returned no clips for {}", attempt, movie_title));
+                attempts.push(PlanAttempt {
+                    attempt,
+                    clips: 0,
+                    violations: vec!["no plan returned".to_string()],
+                });
+                break;
+            }
 
-        let out_clip_name = format!("{}_clip_{}.mp4", movie_title, clip_index);
-        let out_clip = PathBuf::from(format!("clips/{}", out_clip_name));
-        logi(format!("Building clip {}: {} -> {} sec (narr={:.2}s) => {}", clip_index, start_s, end_s, nar_dur, out_clip.display()));
-        if !ffmpeg::ffmpeg_make_adjusted_clip(movie_path, start_s, end_s, &nar_mp3, nar_dur, &out_clip).await? {
-            logw(format!("Failed to build adjusted clip {}", clip_index));
-            continue;
+            let violations = planner::plan_violations(&candidate, &req, movie_duration);
+            for violation in &violations {
+                logw(format!("Plan attempt {} ({}): {}", attempt, movie_title, violation));
+            }
+            attempts.push(PlanAttempt {
+                attempt,
+                clips: candidate.items.len(),
+                violations: violations.clone(),
+            });
+
+            let clean = violations.is_empty();
+            if best.as_ref().is_none_or(|(_, fewest)| violations.len() < *fewest) {
+                best = Some((candidate.clone(), violations.len()));
+            }
+            if clean {
+                logok(format!("Plan attempt {} for {} follows every rule", attempt, movie_title));
+                break;
+            }
+            if attempt < max_attempts {
+                feedback = Some(PlanFeedback {
+                    previous_json: candidate.to_json()?,
+                    violations,
+                });
+            }
         }
 
-        listf
-            .write_all(format!("file '{}'\n", out_clip_name).as_bytes())
-            .await?;
-        made += 1;
-        logok(format!("Built clip {} OK: {}", clip_index, out_clip.display()));
+        let Some((best_plan, _)) = best else {
+            logw(format!("No plan returned for {}", movie_title));
+            return Ok(None);
+        };
+        plan = best_plan;
+        if !repair_plan(&mut plan, movie_duration, movie_title) {
+            logw(format!("Clip plan for {} had no usable clips", movie_title));
+            return Ok(None);
+        }
+        snap_plan_to_cuts(pipe, &inputs.movie_file, &mut plan, movie_title).await;
+
+        let provenance = PlanProvenance {
+            movie_title: movie_title.to_string(),
+            movie_file: inputs.movie_file.display().to_string(),
+            planner: planner.describe(),
+            num_clips,
+            subtitles_file: inputs.subs_file.display().to_string(),
+            subtitles_hash: inputs.subs_hash.clone(),
+            script_file: (!base.script_text.is_empty()).then(|| inputs.script_file.display().to_string()),
+            beats_file: beats.as_ref().map(|(path, _)| path.display().to_string()),
+            style: (!style.name.is_empty()).then(|| style.name.clone()),
+            language: (!style.language.code.is_empty()).then(|| style.language.code.clone()),
+            prompt_template: pipe.plan_template_file.as_ref().map(|path| path.display().to_string()),
+            opening_line: planner::opening_line(&base),
+            created_at: chrono::Local::now().to_rfc3339(),
+        };
+        write_entire_file(&plan_json, plan.to_json_with_inputs(&provenance)?.as_bytes()).await?;
+        job.mark_complete(job::STAGE_PLAN, &plan_hash, &[&plan_json]).await?;
     }
-    listf.flush().await?;
 
-    if made == 0 {
-        logw(format!("No clips produced for {}", movie_title));
-        return Ok(false);
+    Ok(Some((plan, plan_hash)))
+}
+
+/// Looks for black bars baked into `movie_path`; `Ok(None)` when the picture fills the frame.
+async fn detect_letterbox(movie_path: &Path, cfg: &LetterboxConfig) -> Result<Option<CropRect>> {
+    let duration = probe_movie_duration(movie_path).await.unwrap_or(0.0);
+    logi(format!("Checking {} for black bars", movie_path.display()));
+    let area = ffmpeg::ffmpeg_detect_letterbox(movie_path, duration, cfg.black_level).await?;
+    match &area {
+        Some(area) => logok(format!("Black bars found; keeping the {}x{} picture at {},{}", area.w, area.h, area.x, area.y)),
+        None => logok("No black bars found."),
     }
-    logok(format!("Clips produced: {} (concat list: {})", made, concat_list_path.display()));
+    Ok(area)
+}
 
-    let tmp_concat = PathBuf::from(format!("clips/{}_concat_tmp.mp4", movie_title));
-    logi(format!("Concatenating clips -> {}", tmp_concat.display()));
-    if !ffmpeg::ffmpeg_concat_videos(&concat_list_path, &tmp_concat).await? {
-        logw(format!("Concat failed for {}", movie_title));
-        return Ok(false);
+/// The source's picture area inside any black bars, measured once per source movie and kept in
+/// `scripts/srt_files/{title}_area.json`.
+async fn active_area(pipe: &Pipeline, movie_title: &str, movie_path: &Path, movie_fp: &str) -> Result<Option<CropRect>> {
+    if !pipe.letterbox.enabled {
+        return Ok(None);
+    }
+    let settings = pipe.letterbox.black_level.to_string();
+    let cache = ActiveArea::cache_path(movie_title);
+    if let Some(known) = ActiveArea::load(&cache, movie_fp, &settings).await {
+        return Ok(known.crop);
+    }
+
+    let detected = {
+        let _permit = pipe.limits.ffmpeg.acquire().await.context("ffmpeg limiter closed")?;
+        detect_letterbox(movie_path, &pipe.letterbox).await
+    };
+    // Failures are not cached, so the next run tries again.
+    let crop = match detected {
+        Ok(crop) => crop,
+        Err(err) => {
+            logw(format!("Black bar detection failed for {} ({}); rendering the full frame.", movie_path.display(), err));
+            return Ok(None);
+        }
+    };
+    ActiveArea {
+        source: movie_fp.to_string(),
+        settings,
+        crop,
+    }
+    .save(&cache)
+    .await?;
+    Ok(crop)
+}
+
+/// Crop trajectory for the vertical render, analysed from the concatenated video (or reused
+/// from `{title}_reframe.json`), with the hash it was built from. `None` keeps the centre crop.
+async fn reframe_path(
+    pipe: &Pipeline,
+    job: &mut JobManifest,
+    work_dir: &Path,
+    movie_title: &str,
+    tmp_concat: &Path,
+    concat_hash: &str,
+) -> Result<Option<(Vec<CropPoint>, String)>> {
+    let path_json = work_dir.join(format!("{}_reframe.json", movie_title));
+    let reframe_hash = InputHasher::new()
+        .str(concat_hash)
+        .str(&serde_json::to_string(&pipe.vertical)?)
+        .finish();
+
+    if job.is_complete(job::STAGE_REFRAME, &reframe_hash).await {
+        if let Some(points) = reframe::load_path(&path_json).await {
+            logok(format!("Reusing reframing path: {}", path_json.display()));
+            return Ok(Some((points, reframe_hash)));
+        }
     }
-    logok(format!("Concat OK: {}", tmp_concat.display()));
+
+    logi(format!("Analysing motion for vertical reframing: {}", tmp_concat.display()));
+    let analysed = {
+        let _permit = pipe.limits.ffmpeg.acquire().await.context("ffmpeg limiter closed")?;
+        reframe::analyze_crop_path(tmp_concat, None, &pipe.vertical).await
+    };
+    let points = match analysed {
+        Ok(points) if !points.is_empty() => points,
+        Ok(_) => {
+            logw(format!("Reframing analysis of {} returned no frames; using a centre crop.", movie_title));
+            return Ok(None);
+        }
+        Err(err) => {
+            logw(format!("Reframing analysis failed for {} ({}); using a centre crop.", movie_title, err));
+            return Ok(None);
+        }
+    };
+    let cuts = points.iter().filter(|p| p.cut).count();
+    write_entire_file(&path_json, serde_json::to_string(&points)?.as_bytes()).await?;
+    job.mark_complete(job::STAGE_REFRAME, &reframe_hash, &[&path_json]).await?;
+    logok(format!("Reframing path: {} samples across {} shots", points.len(), cuts));
+    Ok(Some((points, reframe_hash)))
+}
+
+/// How far `render_plan` got with a movie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RenderOutcome {
+    /// Nothing usable was written.
+    Failed,
+    /// An output was written, but clips, the music or the vertical render are missing from it.
+    Incomplete,
+    Complete,
+}
+
+/// Everything after planning: TTS, clip renders, concat, BGM, mix and the vertical cut.
+async fn render_plan(
+    pipe: &Pipeline,
+    job: &mut JobManifest,
+    task: &MovieTask,
+    style: &MovieStyle,
+    plan: &ClipPlanList,
+    plan_hash: &str,
+) -> Result<RenderOutcome> {
+    let movie_path = task.path.as_path();
+    let output_name = task.output_name();
+    let movie_title = output_name.as_str();
+    let work_dir = JobManifest::work_dir(movie_title);
+    let work_dir = work_dir.as_path();
+    let synth = &style.synth;
+    let limits = &pipe.limits;
+    let movie_fp = job::fingerprint_file(movie_path).await?;
+    let area = active_area(pipe, &task.title, movie_path, &movie_fp).await?;
+    // The vertical canvas keeps the source's full height even when black bars are cropped away.
+    let source_h = ffmpeg::ffprobe_video_dimensions(movie_path).await.ok().map(|(_, h)| h);
+    let area_key = area.map(|a| a.filter()).unwrap_or_default();
+
+    let tmp_concat = work_dir.join(format!("{}_concat_tmp.mp4", movie_title));
+    let words_json = work_dir.join(format!("{}_words.json", movie_title));
+    let concat_hash = InputHasher::new()
+        .str(&movie_fp)
+        .str(&area_key)
+        .str(plan_hash)
+        .str(&synth.cache_key())
+        .finish();
+
+    // False once a clip, the music mix or the vertical render is missing from the output.
+    let mut complete = true;
+    let concat_key = if job.is_complete(job::STAGE_CONCAT, &concat_hash).await {
+        logok(format!("Reusing concatenated clips: {}", tmp_concat.display()));
+        concat_hash.clone()
+    } else {
+        let concat_list_path = work_dir.join(format!("{}_concat_list.txt", movie_title));
+
+        let (done_tx, mut done_rx) = mpsc::unbounded_channel::<StageDone>();
+        let mut tasks = JoinSet::new();
+        let total = plan.items.len();
+        for (idx, clip) in plan.items.iter().enumerate() {
+            let start_s = clip.start;
+            let end_s = clip.end;
+            let clip_index = idx + 1;
+
+            let tts_hash = InputHasher::new()
+                .str(&clip.narration)
+                .str(&synth.cache_key())
+                .finish();
+            let render_hash = InputHasher::new()
+                .str(&movie_fp)
+                .str(&area_key)
+                .num(start_s)
+                .num(end_s)
+                .str(&tts_hash)
+                .finish();
+            let tts_stage = job::tts_stage(clip_index);
+            let render_stage = job::render_stage(clip_index);
+
+            let work = ClipWork {
+                movie_title: movie_title.to_string(),
+                movie_path: movie_path.to_path_buf(),
+                work_dir: work_dir.to_path_buf(),
+                clip_index,
+                total,
+                start_s,
+                end_s,
+                area,
+                narration: clip.narration.clone(),
+                nar_mp3: work_dir.join("audio").join(format!("{}_audio_{}.mp3", movie_title, clip_index)),
+                out_clip_name: format!("{}_clip_{}.mp4", movie_title, clip_index),
+                tts_cached: job.is_complete(&tts_stage, &tts_hash).await,
+                render_cached: job.is_complete(&render_stage, &render_hash).await,
+                tts_stage,
+                tts_hash,
+                render_stage,
+                render_hash,
+            };
+            tasks.spawn(build_clip(work, Arc::clone(synth), limits.clone(), done_tx.clone()));
+        }
+        drop(done_tx);
+
+        // Record each stage as soon as it lands so a crash mid-batch keeps finished TTS/renders.
+        while let Some(done) = done_rx.recv().await {
+            job.mark_complete(&done.stage, &done.input_hash, &[&done.output]).await?;
+        }
+
+        let mut built: Vec<BuiltClip> = Vec::new();
+        let mut first_err: Option<anyhow::Error> = None;
+        while let Some(joined) = tasks.join_next().await {
+            match joined.context("Clip task panicked")? {
+                Ok(Some(entry)) => built.push(entry),
+                Ok(None) => {}
+                Err(err) => {
+                    first_err.get_or_insert(err);
+                }
+            }
+        }
+        if let Some(err) = first_err {
+            return Err(err);
+        }
+        built.sort_by_key(|clip| clip.clip_index);
+
+        let mut listf = fs::File::create(&concat_list_path).await?;
+        for clip in &built {
+            listf
+                .write_all(format!("file '{}'\n", clip.out_clip_name).as_bytes())
+                .await?;
+        }
+        listf.flush().await?;
+
+        // Word timings on the concatenated timeline, one list per clip, for the captions.
+        let mut offset = 0.0;
+        let mut timeline = Vec::with_capacity(built.len());
+        for clip in &built {
+            timeline.push(captions::shift_words(&clip.words, offset, clip.duration));
+            offset += clip.duration;
+        }
+        captions::save_words(&words_json, &timeline).await?;
+        let estimated = built.iter().filter(|clip| clip.words_estimated).count();
+        if estimated > 0 && pipe.captions.enabled {
+            logi(format!(
+                "{} reported no word timings for {} of {} clips; caption timing is estimated there.",
+                synth.describe(),
+                estimated,
+                built.len()
+            ));
+        }
+
+        let made = built.len();
+
+        if made == 0 {
+            logw(format!("No clips produced for {}", movie_title));
+            return Ok(RenderOutcome::Failed);
+        }
+        logok(format!("Clips produced: {} (concat list: {})", made, concat_list_path.display()));
+
+        logi(format!("Concatenating clips -> {}", tmp_concat.display()));
+        if !ffmpeg::ffmpeg_concat_videos(&concat_list_path, &tmp_concat).await? {
+            logw(format!("Concat failed for {}", movie_title));
+            return Ok(RenderOutcome::Failed);
+        }
+        // A concat missing clips is not recorded, so the next run retries the clips that failed.
+        let concat_key = if made == total {
+            job.mark_complete(job::STAGE_CONCAT, &concat_hash, &[&tmp_concat, &concat_list_path, &words_json]).await?;
+            concat_hash.clone()
+        } else {
+            logw(format!("{} of {} clips failed for {}; the output is incomplete.", total - made, total, movie_title));
+            complete = false;
+            // Later stages are keyed on the clips actually joined, so they are redone once the gaps are filled.
+            let list = read_entire_file(&concat_list_path).await.unwrap_or_default();
+            InputHasher::new().str(&concat_hash).str(&list).finish()
+        };
+        logok(format!("Concat OK: {}", tmp_concat.display()));
+        concat_key
+    };
 
     let final_dur = match ffmpeg::ffprobe_duration_seconds(&tmp_concat).await {
         Ok(v) => v,
         Err(_) => {
             logw(format!("Bad final duration for {}", movie_title));
-            return Ok(false);
+            return Ok(RenderOutcome::Failed);
         }
     };
     logok(format!("Final duration: {:.2} seconds", final_dur));
 
-    let songs = list_files_with_ext(Path::new("backgroundmusic"), ".mp3", ".m4a").await?;
-    if songs.is_empty() {
-        logw("No backgroundmusic files found; output will be narration-only.".to_string());
-        let out_final_only = PathBuf::from(format!("output/{}.mp4", movie_title));
-        let _ = fs::rename(&tmp_concat, &out_final_only).await;
-        logok(format!("Wrote output (no BGM): {}", out_final_only.display()));
+    let out_final = PathBuf::from(format!("output/{}.mp4", movie_title));
+    let bgm_out = work_dir.join(format!("{}_bgm.m4a", movie_title));
+    let bgm_hash = InputHasher::new().str(&concat_key).str(&style.preset.bgm_mood).finish();
+
+    let sidecars = &pipe.narration_subtitles;
+    let out_srt = PathBuf::from(format!("output/{}.srt", movie_title));
+    let out_vtt = PathBuf::from(format!("output/{}.vtt", movie_title));
+    let sidecar_hash = InputHasher::new().str(&concat_key).num(sidecars.max_cue_chars).finish();
+    let mut have_srt = false;
+    if sidecars.enabled {
+        if job.is_complete(job::STAGE_SIDECARS, &sidecar_hash).await {
+            logok(format!("Reusing narration subtitles: {}", out_srt.display()));
+            have_srt = true;
+        } else if let Some(timeline) = captions::load_words::<Vec<Vec<WordTiming>>>(&words_json).await {
+            let cues = captions::narration_cues(&timeline, sidecars.max_cue_chars);
+            write_entire_file(&out_srt, captions::to_srt(&cues).as_bytes()).await?;
+            write_entire_file(&out_vtt, captions::to_vtt(&cues).as_bytes()).await?;
+            job.mark_complete(job::STAGE_SIDECARS, &sidecar_hash, &[&out_srt, &out_vtt]).await?;
+            logok(format!("Wrote narration subtitles ({} cues): {} / {}", cues.len(), out_srt.display(), out_vtt.display()));
+            have_srt = true;
+        } else {
+            logw(format!("No word timings for {}; skipping narration subtitle files.", movie_title));
+        }
+    }
+    let mux_srt = have_srt && sidecars.mux;
+    let mix_hash = InputHasher::new()
+        .str(&concat_key)
+        .str(&bgm_hash)
+        .str(if mux_srt { &sidecar_hash } else { "" })
+        .finish();
+
+    // Narration-only fallbacks are not recorded, so music added later still gets mixed in.
+    let mut mix_complete = true;
+    // Set when music or subtitles were meant to go in and did not; without any music files
+    // there is nothing to retry, so a narration-only output still counts as complete.
+    let mut mix_failed = false;
+    if job.is_complete(job::STAGE_MIX, &mix_hash).await {
+        logok(format!("Reusing mixed output: {}", out_final.display()));
     } else {
-        let mut rng = rand::rngs::StdRng::seed_from_u64(now_seed());
-        let bgm_list = PathBuf::from(format!("clips/{}_bgm_list.txt", movie_title));
-        let mut bgml = fs::File::create(&bgm_list).await?;
-
-        logi(format!("Building BGM track list ({} songs available)...", songs.len()));
-
-        let mut covered = 0.0;
-        let mut part = 0;
-        while covered + 0.01 < final_dur {
-            let idx = rng.gen_range(0..songs.len());
-            let song = &songs[idx];
-            let sd = match ffmpeg::ffprobe_duration_seconds(song).await {
-                Ok(v) => v,
-                Err(_) => continue,
-            };
-            if sd <= 60.0 {
-                continue;
-            }
-            let start = 40.0;
-            let avail = sd - start;
-            if avail <= 1.0 {
-                continue;
+        let mut have_bgm = job.is_complete(job::STAGE_BGM, &bgm_hash).await;
+        if have_bgm {
+            logok(format!("Reusing BGM track: {}", bgm_out.display()));
+        } else {
+            let songs = bgm_candidates(&style.preset.bgm_mood).await?;
+            if songs.is_empty() {
+                logw("No backgroundmusic files found; output will be narration-only.");
+            } else {
+                have_bgm = build_bgm_track(work_dir, movie_title, &songs, final_dur, &bgm_out, pipe.seed).await?;
+                if have_bgm {
+                    job.mark_complete(job::STAGE_BGM, &bgm_hash, &[&bgm_out]).await?;
+                } else {
+                    mix_failed = true;
+                }
             }
-            let need = final_dur - covered;
-            let take = if avail < need { avail } else { need };
+        }
 
-            let part_name = format!("{}_bgm_part_{}.m4a", movie_title, part + 1);
-            let part_path = PathBuf::from(format!("clips/{}", part_name));
+        if !have_bgm {
+            let _ = fs::copy(&tmp_concat, &out_final).await;
+            mix_complete = false;
+            logok(format!("Wrote output (no BGM): {}", out_final.display()));
+        } else {
+            logi(format!("Mixing narration + BGM -> {}", out_final.display()));
+            if !ffmpeg::ffmpeg_mix_bgm(&tmp_concat, &bgm_out, &out_final).await? {
+                logw("Mix failed; output narration-only.");
+                let _ = fs::copy(&tmp_concat, &out_final).await;
+                mix_complete = false;
+                mix_failed = true;
+            }
+            logok(format!("Wrote output: {}", out_final.display()));
+        }
 
-            if !ffmpeg::ffmpeg_trim_audio(song, start, take, &part_path).await? {
-                continue;
+        if mux_srt {
+            let muxed = work_dir.join(format!("{}_muxed.mp4", movie_title));
+            let language = captions::iso639_2(&style.language.code);
+            if ffmpeg::ffmpeg_mux_soft_subtitles(&out_final, &out_srt, language, &muxed).await? {
+                if fs::rename(&muxed, &out_final).await.is_err() {
+                    fs::copy(&muxed, &out_final)
+                        .await
+                        .with_context(|| format!("Failed to replace {}", out_final.display()))?;
+                }
+                logok(format!("Added soft subtitle track: {}", out_final.display()));
+            } else {
+                logw(format!("Output has no soft subtitle track; {} is still available.", out_srt.display()));
+                mix_complete = false;
+                mix_failed = true;
             }
-            bgml
-                .write_all(format!("file '{}'\n", part_name).as_bytes())
-                .await?;
-            covered += take;
-            part += 1;
-            if part > 200 {
-                break;
+        }
+        if mix_complete {
+            job.mark_complete(job::STAGE_MIX, &mix_hash, &[&out_final]).await?;
+        }
+    }
+    complete &= !mix_failed;
+
+    let out_vert = PathBuf::from(format!("tiktok_output/{}_vertical.mp4", movie_title));
+    let timeline: Option<Vec<Vec<WordTiming>>> = if pipe.captions.enabled {
+        let loaded = captions::load_words(&words_json).await;
+        if loaded.is_none() {
+            logw(format!("No word timings for {}; the vertical render will have no captions.", movie_title));
+        }
+        loaded
+    } else {
+        None
+    };
+    let captions_key = match &timeline {
+        Some(_) => serde_json::to_string(&pipe.captions)?,
+        None => String::new(),
+    };
+    let crop_path = if pipe.vertical.reframe && pipe.vertical.layout != VerticalLayout::Blur {
+        reframe_path(pipe, job, work_dir, movie_title, &tmp_concat, &concat_key).await?
+    } else {
+        None
+    };
+    let reframe_key = match &crop_path {
+        Some((_, hash)) => hash.as_str(),
+        None => "",
+    };
+    let vertical_hash = InputHasher::new()
+        .str(&mix_hash)
+        .num(mix_complete)
+        .str(&captions_key)
+        .str(reframe_key)
+        .str(&format!("{:?}", pipe.vertical.layout))
+        .num(pipe.vertical.background_blur)
+        .num(pipe.vertical.background_brightness)
+        .num(source_h.unwrap_or(0))
+        .finish();
+    if job.is_complete(job::STAGE_VERTICAL, &vertical_hash).await {
+        logok(format!("Reusing vertical render: {}", out_vert.display()));
+    } else {
+        let ass_path = work_dir.join(format!("{}_captions.ass", movie_title));
+        let font_file = Path::new(&pipe.captions.font_file);
+        let fonts_dir = font_file.parent().unwrap_or(Path::new("."));
+        let burn = match &timeline {
+            Some(timeline) => {
+                if !file_exists(font_file).await {
+                    logw(format!("Caption font {} not found; libass will substitute another font.", font_file.display()));
+                }
+                write_entire_file(&ass_path, captions::build_ass(timeline, &pipe.captions).as_bytes()).await?;
+                Some(BurnIn {
+                    ass: &ass_path,
+                    fonts_dir,
+                })
             }
+            None => None,
+        };
+        let commands_file = work_dir.join(format!("{}_reframe.cmd", movie_title));
+        let commands = crop_path.as_ref().map(|(points, _)| reframe::command_points(points));
+        let follow = commands.as_deref().map(|points| CropPath {
+            points,
+            commands_file: &commands_file,
+        });
+        logi(format!("Rendering vertical -> {}", out_vert.display()));
+        // No black bars to remove here: the clips were already cropped to the active area.
+        if !ffmpeg::ffmpeg_make_vertical(&out_final, &out_vert, &pipe.vertical, None, source_h, follow.as_ref(), burn.as_ref()).await? {
+            logw(format!("Vertical render failed for {}", movie_title));
+            complete = false;
+        } else {
+            job.mark_complete(job::STAGE_VERTICAL, &vertical_hash, &[&out_vert]).await?;
+            logok(format!("Vertical render OK: {}", out_vert.display()));
         }
-        bgml.flush().await?;
+    }
 
-        logok(format!("BGM parts created: {} (covered {:.2}s / {:.2}s)", part, covered, final_dur));
+    Ok(if complete { RenderOutcome::Complete } else { RenderOutcome::Incomplete })
+}
 
-        let bgm_out = PathBuf::from(format!("clips/{}_bgm.m4a", movie_title));
-        logi(format!("Concatenating BGM -> {}", bgm_out.display()));
-        if !ffmpeg::ffmpeg_concat_audio(&bgm_list, &bgm_out).await? {
-            logw("BGM concat failed; output narration-only.".to_string());
-            let out_final_only = PathBuf::from(format!("output/{}.mp4", movie_title));
-            let _ = fs::rename(&tmp_concat, &out_final_only).await;
-            logok(format!("Wrote output (no BGM): {}", out_final_only.display()));
-        } else {
-            logok(format!("BGM concat OK: {}", bgm_out.display()));
-            let out_final_only = PathBuf::from(format!("output/{}.mp4", movie_title));
-            logi(format!("Mixing narration + BGM -> {}", out_final_only.display()));
-            if !ffmpeg::ffmpeg_mix_bgm(&tmp_concat, &bgm_out, &out_final_only).await? {
-                logw("Mix failed; output narration-only.".to_string());
-                let _ = fs::rename(&tmp_concat, &out_final_only).await;
+async fn process_movie(pipe: &Pipeline, task: &MovieTask, report: &mut MovieReport) -> Result<bool> {
+    let movie_path = task.path.as_path();
+    let movie_title = task.title.as_str();
+    let output_name = task.output_name();
+    let work_dir = JobManifest::work_dir(&output_name);
+    ensure_movie_dirs(&work_dir).await?;
+
+    let mut job = open_job(&output_name).await;
+    let style = resolve_style(pipe, movie_title, &task.language);
+
+    let (plan, plan_hash) = match &task.plan {
+        Some(plan) => {
+            logi(format!("Using supplied clip plan for {} ({} clips); skipping the planner.", movie_title, plan.items.len()));
+            let mut plan = plan.clone();
+            if !repair_plan(&mut plan, probe_movie_duration(movie_path).await, movie_title) {
+                return Ok(false);
+            }
+            // The snapped plan goes to the work dir, so the moves can be reviewed and a resumed
+            // render does not scan for cuts again.
+            let plan_json = work_plan_path(&work_dir, &output_name);
+            let snap_hash = InputHasher::new()
+                .str(&plan.to_json()?)
+                .str(&cut_snap_key(&pipe.cut_snap))
+                .finish();
+            let saved = if job.is_complete(job::STAGE_PLAN, &snap_hash).await {
+                read_entire_file(&plan_json).await.and_then(|text| ClipPlanList::from_json(&text)).ok()
             } else {
-                let _ = fs::remove_file(&tmp_concat).await;
+                None
+            };
+            match saved {
+                Some(saved) => {
+                    logok(format!("Reusing snapped clip plan: {}", plan_json.display()));
+                    plan = saved;
+                }
+                None => {
+                    snap_plan_to_cuts(pipe, movie_path, &mut plan, movie_title).await;
+                    let json = match &task.plan_inputs {
+                        Some(inputs) => plan.to_json_with_inputs(inputs)?,
+                        None => plan.to_json()?,
+                    };
+                    write_entire_file(&plan_json, json.as_bytes()).await?;
+                    job.mark_complete(job::STAGE_PLAN, &snap_hash, &[&plan_json]).await?;
+                }
             }
-            logok(format!("Wrote output: {}", out_final_only.display()));
+            let plan_hash = job::hash_str(&plan.to_json()?);
+            (plan, plan_hash)
+        }
+        None => {
+            let num_clips = match job.num_clips {
+                Some(n) if !task.fixed_clips || n == task.num_clips => n,
+                _ => {
+                    job.set_num_clips(task.num_clips).await?;
+                    task.num_clips
+                }
+            };
+            let Some(inputs) = load_plan_inputs(pipe, &mut job, movie_path, movie_title, report).await? else {
+                return Ok(false);
+            };
+            match obtain_plan(pipe, &mut job, task, &style, &inputs, num_clips, &mut report.plan_attempts).await? {
+                Some(found) => found,
+                None => return Ok(false),
+            }
+        }
+    };
+
+    if let Some(dest) = &task.plan_out {
+        let plan_json = work_plan_path(&work_dir, &output_name);
+        if let Some(parent) = dest.parent() {
+            ensure_dir(parent).await?;
         }
+        fs::copy(&plan_json, dest)
+            .await
+            .with_context(|| format!("Failed to copy plan to {}", dest.display()))?;
+        logok(format!("Plan saved for review: {} ({} clips)", dest.display(), plan.items.len()));
+        return Ok(true);
     }
 
-    let out_final = PathBuf::from(format!("output/{}.mp4", movie_title));
-    let out_vert = PathBuf::from(format!("tiktok_output/{}_vertical.mp4", movie_title));
-    logi(format!("Rendering vertical -> {}", out_vert.display()));
-    if !ffmpeg::ffmpeg_make_vertical(&out_final, &out_vert).await? {
-        logw(format!("Vertical render failed for {}", movie_title));
-    } else {
-        logok(format!("Vertical render OK: {}", out_vert.display()));
+    match render_plan(pipe, &mut job, task, &style, &plan, &plan_hash).await? {
+        RenderOutcome::Complete => {}
+        RenderOutcome::Failed => return Ok(false),
+        RenderOutcome::Incomplete => {
+            // Keeping the work dir keeps the job manifest, so the next run redoes only what is missing.
+            logw(format!(
+                "{} is incomplete; keeping {} and the source movie so the next run retries it.",
+                output_name,
+                work_dir.display()
+            ));
+            report.error = Some(format!("incomplete output; {} kept for the next run", work_dir.display()));
+            return Ok(false);
+        }
+    }
+
+    if task.retire {
+        let retired = match movie_path.file_name() {
+            Some(name) => Path::new("movies_retired").join(name),
+            None => PathBuf::from(format!("movies_retired/{}.mp4", movie_title)),
+        };
+        let _ = fs::rename(movie_path, &retired).await;
+        logok(format!("Retired source movie -> {}", retired.display()));
+    }
+
+    if let Err(err) = fs::remove_dir_all(&work_dir).await {
+        logw(format!("Failed to clean up {}: {}", work_dir.display(), err));
+    }
+
+    Ok(true)
+}
+
+async fn build_bgm_track(
+    work_dir: &Path,
+    movie_title: &str,
+    songs: &[PathBuf],
+    final_dur: f64,
+    bgm_out: &Path,
+    seed: u64,
+) -> Result<bool> {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+    let bgm_list = work_dir.join(format!("{}_bgm_list.txt", movie_title));
+    let mut bgml = fs::File::create(&bgm_list).await?;
+
+    logi(format!("Building BGM track list ({} songs available)...", songs.len()));
+
+    let mut covered = 0.0;
+    let mut part = 0;
+    let mut part_paths = Vec::new();
+    while covered + 0.01 < final_dur {
+        let idx = rng.gen_range(0..songs.len());
+        let song = &songs[idx];
+        let sd = match ffmpeg::ffprobe_duration_seconds(song).await {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        if sd <= 60.0 {
+            continue;
+        }
+        let start = 40.0;
+        let avail = sd - start;
+        if avail <= 1.0 {
+            continue;
+        }
+        let need = final_dur - covered;
+        let take = if avail < need { avail } else { need };
+
+        let part_name = format!("{}_bgm_part_{}.m4a", movie_title, part + 1);
+        let part_path = work_dir.join(&part_name);
+
+        if !ffmpeg::ffmpeg_trim_audio(song, start, take, &part_path).await? {
+            continue;
+        }
+        bgml
+            .write_all(format!("file '{}'\n", part_name).as_bytes())
+            .await?;
+        part_paths.push(part_path);
+        covered += take;
+        part += 1;
+        if part > 200 {
+            break;
+        }
     }
+    bgml.flush().await?;
 
-    let retired = PathBuf::from(format!("movies_retired/{}.mp4", movie_title));
-    let _ = fs::rename(movie_path, &retired).await;
-    logok(format!("Retired source movie -> {}", retired.display()));
+    logok(format!("BGM parts created: {} (covered {:.2}s / {:.2}s)", part, covered, final_dur));
 
+    logi(format!("Concatenating BGM -> {}", bgm_out.display()));
+    let ok = ffmpeg::ffmpeg_concat_audio(&bgm_list, bgm_out).await?;
+    for part_path in &part_paths {
+        let _ = fs::remove_file(part_path).await;
+    }
+    let _ = fs::remove_file(&bgm_list).await;
+
+    if !ok {
+        logw("BGM concat failed; output narration-only.");
+        return Ok(false);
+    }
+    logok(format!("BGM concat OK: {}", bgm_out.display()));
     Ok(true)
 }
 
+/// Target language of one recap.
+struct Language {
+    /// Code from `languages.targets`; empty for the default English recap.
+    code: String,
+    /// Name used in the planning prompt.
+    name: String,
+    /// Required start of the first narration; empty disables the check.
+    opening_line: String,
+}
+
+/// Style preset and language in effect for one recap, with the narration voice they ask for.
+struct MovieStyle {
+    /// Preset name; empty for the built-in style.
+    name: String,
+    preset: StylePreset,
+    language: Language,
+    synth: Arc<dyn NarrationSynth>,
+}
+
+fn resolve_style(pipe: &Pipeline, movie_title: &str, language: &str) -> MovieStyle {
+    let (name, preset, synth) = resolve_preset(pipe, movie_title);
+    let (language, synth) = resolve_language(pipe, language, synth);
+    logi(format!(
+        "Narration: {} style in {} (voice: {})",
+        if name.is_empty() { "built-in" } else { &name },
+        language.name,
+        synth.describe()
+    ));
+    MovieStyle {
+        name,
+        preset,
+        language,
+        synth,
+    }
+}
+
+/// Run-wide `--style` first, then `styles.per_movie`, then `styles.default`.
+fn resolve_preset(pipe: &Pipeline, movie_title: &str) -> (String, StylePreset, Arc<dyn NarrationSynth>) {
+    let styles = &pipe.styles;
+    let name = pipe
+        .style_override
+        .clone()
+        .or_else(|| styles.per_movie.get(movie_title).cloned())
+        .unwrap_or_else(|| styles.default.clone());
+
+    let builtin = (String::new(), StylePreset::default(), Arc::clone(&pipe.synth));
+    if name.is_empty() {
+        return builtin;
+    }
+    let Some(preset) = styles.presets.get(&name) else {
+        logw(format!("Unknown style preset \"{}\"; using the built-in style.", name));
+        return builtin;
+    };
+
+    let synth = if preset.voice_id.is_empty() {
+        Arc::clone(&pipe.synth)
+    } else {
+        match pipe.synth.with_voice(&preset.voice_id) {
+            Some(synth) => synth,
+            None => {
+                logw(format!("{} cannot switch voices; style \"{}\" keeps the configured voice.", pipe.synth.describe(), name));
+                Arc::clone(&pipe.synth)
+            }
+        }
+    };
+    (name, preset.clone(), synth)
+}
+
+/// Prompt name, opening line and voice for `code`, starting from the style's voice.
+fn resolve_language(pipe: &Pipeline, code: &str, synth: Arc<dyn NarrationSynth>) -> (Language, Arc<dyn NarrationSynth>) {
+    let english = config::is_english(code);
+    if code.is_empty() {
+        let language = Language {
+            code: String::new(),
+            name: "English".to_string(),
+            opening_line: pipe.planning.opening_line.clone(),
+        };
+        return (language, synth);
+    }
+
+    let profile = pipe.languages.profiles.get(code).cloned().unwrap_or_default();
+    let name = match (profile.name.is_empty(), english) {
+        (false, _) => profile.name.clone(),
+        (true, true) => "English".to_string(),
+        (true, false) => code.to_string(),
+    };
+    let opening_line = if !profile.opening_line.is_empty() {
+        profile.opening_line.clone()
+    } else if english {
+        pipe.planning.opening_line.clone()
+    } else {
+        logw(format!("No opening line configured for language {}; the first narration will not be checked.", code));
+        String::new()
+    };
+
+    let synth = if english && profile.voice_id.is_empty() && profile.model_id.is_empty() {
+        synth
+    } else {
+        match synth.with_language(code, &profile) {
+            Some(localized) => localized,
+            None => {
+                logw(format!("{} cannot switch to language {}; narration keeps the configured voice.", synth.describe(), code));
+                synth
+            }
+        }
+    };
+    let language = Language {
+        code: code.to_string(),
+        name,
+        opening_line,
+    };
+    (language, synth)
+}
+
+/// `{title}_{code}` for a localized recap, the plain title for the default one.
+fn localized_name(movie_title: &str, language: &str) -> String {
+    if language.is_empty() {
+        movie_title.to_string()
+    } else {
+        format!("{}_{}", movie_title, language)
+    }
+}
+
+/// Tracks in `backgroundmusic/{mood}/`, or the whole folder when the mood is unset or empty.
+async fn bgm_candidates(mood: &str) -> Result<Vec<PathBuf>> {
+    if !mood.is_empty() {
+        let dir = Path::new("backgroundmusic").join(mood);
+        if dir_exists(&dir).await {
+            let songs = list_files_with_ext(&dir, ".mp3", ".m4a").await?;
+            if !songs.is_empty() {
+                logi(format!("Background music mood: {} ({} tracks)", mood, songs.len()));
+                return Ok(songs);
+            }
+        }
+        logw(format!("No tracks in backgroundmusic/{}/; using the whole backgroundmusic folder.", mood));
+    }
+    list_files_with_ext(Path::new("backgroundmusic"), ".mp3", ".m4a").await
+}
+
+/// Planner output inside the work dir; doubles as the resume cache for `STAGE_PLAN`.
+fn work_plan_path(work_dir: &Path, movie_title: &str) -> PathBuf {
+    work_dir.join(format!("{}_plan.json", movie_title))
+}
+
+impl Pipeline {
+    /// Language codes to narrate every movie in; a single empty code for the default English recap.
+    fn target_languages(&self) -> Vec<String> {
+        if self.languages.targets.is_empty() {
+            vec![String::new()]
+        } else {
+            self.languages.targets.clone()
+        }
+    }
+
+    /// The one language used by single-movie commands: the first target.
+    fn single_language(&self) -> String {
+        let targets = self.target_languages();
+        if targets.len() > 1 {
+            logw(format!("Several target languages configured; using {} (pick another with --lang).", targets[0]));
+        }
+        targets[0].clone()
+    }
+}
+
+/// Where plan-only runs leave plans for a human to review and edit.
+pub fn review_plan_path(movie_title: &str) -> PathBuf {
+    Path::new("plans").join(format!("{}.plan.json", movie_title))
+}
+
+/// A finished output only counts once its job manifest is gone; a leftover manifest
+/// means the previous run died part-way and the movie should be resumed.
 fn output_already_exists(movie_title: &str) -> bool {
     let out = PathBuf::from(format!("output/{}.mp4", movie_title));
-    out.exists()
+    out.exists() && !JobManifest::manifest_path(movie_title).exists()
+}
+
+fn is_movie_file(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .and_then(OsStr::to_str)
+            .map(|ext| MOVIE_EXTENSIONS.iter().any(|m| ext.eq_ignore_ascii_case(m)))
+            .unwrap_or(false)
 }
 
 fn strip_ext(filename: &str) -> String {
@@ -755,12 +1862,271 @@ fn strip_ext(filename: &str) -> String {
         .to_string()
 }
 
-pub async fn run_generation() -> Result<i32> {
-    let cfg = Config::load("config.json").await?;
+/// Knobs shared by the GUI, the batch run and the single-stage CLI commands.
+#[derive(Debug, Clone)]
+pub struct GenerationOptions {
+    pub config_path: PathBuf,
+    /// Fixed clip count; random in `MIN_NUM_CLIPS..=MAX_NUM_CLIPS` when unset.
+    pub num_clips: Option<i32>,
+    /// Seed for the clip count and background-music picks; time-based when unset.
+    pub seed: Option<u64>,
+    /// Stop every movie after planning and write `plans/{title}.plan.json`.
+    pub plan_only: bool,
+    /// Prompt template for this run, overriding `planner.prompt_template`.
+    pub prompt_template: Option<String>,
+    /// Style preset for this run, overriding `styles.per_movie` and `styles.default`.
+    pub style: Option<String>,
+    /// Narration languages for this run, overriding `languages.targets`.
+    pub languages: Option<Vec<String>>,
+}
+
+impl Default for GenerationOptions {
+    fn default() -> Self {
+        Self {
+            config_path: PathBuf::from("config.json"),
+            num_clips: None,
+            seed: None,
+            plan_only: false,
+            prompt_template: None,
+            style: None,
+            languages: None,
+        }
+    }
+}
+
+impl GenerationOptions {
+    fn seed(&self) -> u64 {
+        self.seed.unwrap_or_else(now_seed)
+    }
+
+    fn pick_num_clips(&self, seed: u64) -> i32 {
+        match self.num_clips {
+            Some(n) => n.max(1),
+            None => rand::rngs::StdRng::seed_from_u64(seed).gen_range(MIN_NUM_CLIPS..=MAX_NUM_CLIPS),
+        }
+    }
+}
+
+/// Reads `prompts/{name}.txt` (or `name` itself when it is a path). Falls back to the built-in
+/// prompt, returned as an empty string without a file, when nothing is selected or the file is unusable.
+async fn load_plan_template(name: &str) -> (String, Option<PathBuf>) {
+    let name = name.trim();
+    if name.is_empty() {
+        return (String::new(), None);
+    }
+
+    let direct = PathBuf::from(name);
+    let path = if direct.extension().is_some() || direct.components().count() > 1 {
+        direct
+    } else {
+        Path::new("prompts").join(format!("{}.txt", name))
+    };
+
+    match read_entire_file(&path).await {
+        Ok(text) if !text.trim().is_empty() => {
+            for unknown in planner::unknown_placeholders(&text, planner::PLAN_TEMPLATE_VARS) {
+                logw(format!("Prompt template {} uses unknown placeholder {{{{{}}}}}; it is left as-is.", path.display(), unknown));
+            }
+            if !text.contains("{{subs}}") {
+                logw(format!("Prompt template {} has no {{{{subs}}}} placeholder; the planner will not see the subtitles.", path.display()));
+            }
+            if !text.contains("{{opening_line}}") {
+                logw(format!("Prompt template {} has no {{{{opening_line}}}} placeholder; the opening line is not checked.", path.display()));
+            }
+            logi(format!("Using prompt template: {}", path.display()));
+            (text, Some(path))
+        }
+        Ok(_) => {
+            logw(format!("Prompt template {} is empty; using the built-in prompt.", path.display()));
+            (String::new(), None)
+        }
+        Err(err) => {
+            logw(format!("Prompt template {} unreadable ({}); using the built-in prompt.", path.display(), err));
+            (String::new(), None)
+        }
+    }
+}
+
+async fn build_pipeline(opts: &GenerationOptions) -> Result<(Config, Pipeline)> {
+    let cfg = Config::load(&opts.config_path).await?;
     let client = reqwest::Client::builder()
         .cookie_store(true)
         .build()
         .context("Failed to build HTTP client")?;
+    let (plan_template, plan_template_file) =
+        load_plan_template(opts.prompt_template.as_deref().unwrap_or(&cfg.planner.prompt_template)).await;
+    let pipe = Pipeline {
+        seed: opts.seed(),
+        planning: cfg.planner.clone(),
+        styles: cfg.styles.clone(),
+        style_override: opts.style.clone(),
+        languages: LanguageConfig {
+            targets: opts.languages.clone().unwrap_or_else(|| cfg.languages.targets.clone()),
+            ..cfg.languages.clone()
+        },
+        plan_template,
+        plan_template_file,
+        planner: planner::make_planner(&cfg, &client),
+        synth: narration::make_synth(&cfg, &client),
+        subtitles: SubtitleChain::from_config(&cfg, &client),
+        limits: RenderLimits::from_config(&cfg),
+        captions: cfg.captions.clone(),
+        narration_subtitles: cfg.narration_subtitles.clone(),
+        vertical: cfg.vertical.clone(),
+        cut_snap: cfg.cut_snap.clone(),
+        letterbox: cfg.letterbox.clone(),
+        client,
+    };
+    Ok((cfg, pipe))
+}
+
+/// Accepts either a path to a movie file or a title found in `movies/`.
+pub async fn resolve_movie(arg: &str) -> Result<(PathBuf, String)> {
+    let direct = PathBuf::from(arg);
+    if file_exists(&direct).await {
+        let title = strip_ext(&direct.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default());
+        return Ok((direct, title));
+    }
+
+    if let Ok(mut entries) = fs::read_dir("movies").await {
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if !is_movie_file(&path) {
+                continue;
+            }
+            let title = strip_ext(entry.file_name().to_string_lossy().as_ref());
+            if title.eq_ignore_ascii_case(arg) {
+                return Ok((path, title));
+            }
+        }
+    }
+
+    anyhow::bail!("Movie not found: {} (pass a file path or a title from movies/)", arg)
+}
+
+/// Runs subtitles + planning for one movie and writes the plan (with its inputs) to `out`,
+/// or to `plans/{title}.plan.json`. Returns the written path.
+pub async fn plan_single(opts: &GenerationOptions, movie_arg: &str, out: Option<&Path>) -> Result<Option<PathBuf>> {
+    let (_cfg, pipe) = build_pipeline(opts).await?;
+    let (path, title) = resolve_movie(movie_arg).await?;
+    let language = pipe.single_language();
+    let dest = out
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| review_plan_path(&localized_name(&title, &language)));
+    let mut report = MovieReport::new(&title, &path);
+    let task = MovieTask {
+        num_clips: opts.pick_num_clips(pipe.seed),
+        fixed_clips: opts.num_clips.is_some(),
+        path,
+        title,
+        plan: None,
+        plan_inputs: None,
+        retire: false,
+        plan_out: Some(dest.clone()),
+        language,
+    };
+    Ok(process_movie(&pipe, &task, &mut report).await?.then_some(dest))
+}
+
+/// Reads a plan file in the `{"clips":[...]}` shape, e.g. one saved by a plan-only run and edited by hand.
+pub async fn load_plan_file(path: &Path) -> Result<(ClipPlanList, Option<PlanProvenance>)> {
+    let text = read_entire_file(path)
+        .await
+        .with_context(|| format!("Failed to read plan: {}", path.display()))?;
+    let plan = ClipPlanList::from_json(&text).with_context(|| format!("Invalid plan file: {}", path.display()))?;
+    anyhow::ensure!(!plan.items.is_empty(), "Plan file has no clips: {}", path.display());
+    Ok((plan, ClipPlanList::inputs_from_json(&text)))
+}
+
+/// Renders one movie from an existing plan, voiced in the language recorded in the plan's
+/// `inputs`, or else the first target language. The source movie is left in place.
+pub async fn render_single(opts: &GenerationOptions, movie_arg: &str, plan: ClipPlanList, inputs: Option<PlanProvenance>) -> Result<bool> {
+    let (_cfg, pipe) = build_pipeline(opts).await?;
+    let (path, title) = resolve_movie(movie_arg).await?;
+    let mut report = MovieReport::new(&title, &path);
+    let task = MovieTask {
+        num_clips: plan.items.len() as i32,
+        fixed_clips: false,
+        path,
+        title,
+        plan: Some(plan),
+        retire: false,
+        plan_out: None,
+        language: inputs
+            .as_ref()
+            .and_then(|inputs| inputs.language.clone())
+            .unwrap_or_else(|| pipe.single_language()),
+        plan_inputs: inputs,
+    };
+    process_movie(&pipe, &task, &mut report).await
+}
+
+/// Runs only the subtitle provider chain for one movie.
+///
+/// Returns the SRT path (if any provider succeeded) together with every
+/// provider attempt, so the caller can show why each source failed. No job
+/// work dir is created; only `scripts/srt_files` is.
+pub async fn fetch_subtitles_single(
+    opts: &GenerationOptions,
+    movie_arg: &str,
+) -> Result<(Option<PathBuf>, Vec<SubtitleAttempt>)> {
+    let (_cfg, pipe) = build_pipeline(opts).await?;
+    let (movie_path, movie_title) = resolve_movie(movie_arg).await?;
+    ensure_dir(Path::new("scripts/srt_files")).await?;
+
+    let mut attempts = Vec::new();
+    let srt = ensure_subtitles(&pipe, &movie_path, &movie_title, &mut attempts).await?;
+    Ok((srt, attempts))
+}
+
+/// Voices `text` with the configured narration backend.
+pub async fn synthesize_single(opts: &GenerationOptions, text: &str, out_mp3: &Path) -> Result<bool> {
+    let (_cfg, pipe) = build_pipeline(opts).await?;
+    // No movie here, so only `--style` or `styles.default` can pick the voice.
+    let style = resolve_style(&pipe, "", &pipe.single_language());
+    logi(format!("Narration voice: {}", style.synth.describe()));
+    style.synth.synthesize(text, out_mp3).await
+}
+
+/// Converts one horizontal video to the vertical format with the configured layout, following
+/// the action when reframing is on.
+pub async fn vertical_single(opts: &GenerationOptions, input: &Path, output: &Path) -> Result<bool> {
+    let cfg = Config::load(&opts.config_path).await?;
+    let framing = &cfg.vertical;
+    let area = if cfg.letterbox.enabled {
+        detect_letterbox(input, &cfg.letterbox).await.unwrap_or_else(|err| {
+            logw(format!("Black bar detection failed ({}); using the full frame.", err));
+            None
+        })
+    } else {
+        None
+    };
+    let commands_file = output.with_extension("reframe.cmd");
+    let mut commands = None;
+    if framing.reframe && framing.layout != VerticalLayout::Blur {
+        logi(format!("Analysing motion for vertical reframing: {}", input.display()));
+        match reframe::analyze_crop_path(input, area.as_ref(), framing).await {
+            Ok(points) if !points.is_empty() => commands = Some(reframe::command_points(&points)),
+            Ok(_) => logw("Reframing analysis returned no frames; using a centre crop."),
+            Err(err) => logw(format!("Reframing analysis failed ({}); using a centre crop.", err)),
+        }
+    }
+    let follow = commands.as_deref().map(|points| CropPath {
+        points,
+        commands_file: &commands_file,
+    });
+    let ok = ffmpeg::ffmpeg_make_vertical(input, output, framing, area.as_ref(), None, follow.as_ref(), None).await?;
+    let _ = fs::remove_file(&commands_file).await;
+    Ok(ok)
+}
+
+pub async fn run_generation() -> Result<i32> {
+    run_generation_with(&GenerationOptions::default()).await
+}
+
+pub async fn run_generation_with(opts: &GenerationOptions) -> Result<i32> {
+    let (cfg, pipe) = build_pipeline(opts).await?;
+    logi(format!("Narration voice: {}", pipe.synth.describe()));
 
     ensure_dir(Path::new("movies")).await?;
     ensure_dir(Path::new("output")).await?;
@@ -771,47 +2137,193 @@ pub async fn run_generation() -> Result<i32> {
     ensure_dir(Path::new("tiktok_output")).await?;
     ensure_dir(Path::new("movies_retired")).await?;
 
-    logi("Clearing clips/ folder...".to_string());
-    if !clear_directory_contents(Path::new("clips")).await? {
-        logw("Failed to fully clear clips/ (continuing anyway).".to_string());
-    } else {
-        logok("Cleared clips/ folder.".to_string());
-    }
-
-    ensure_dir(Path::new("clips")).await?;
-    ensure_dir(Path::new("clips/audio")).await?;
-
-    let mut rng = rand::rngs::StdRng::seed_from_u64(now_seed());
-    let num_clips = rng.gen_range(MIN_NUM_CLIPS..=MAX_NUM_CLIPS);
+    let num_clips = opts.pick_num_clips(pipe.seed);
+    let fixed_clips = opts.num_clips.is_some();
+    let targets = pipe.target_languages();
 
-    let mut processed = 0;
+    let mut queue: Vec<(PathBuf, String, Vec<String>)> = Vec::new();
     let mut entries = fs::read_dir("movies").await?;
     while let Some(entry) = entries.next_entry().await? {
         let path = entry.path();
-        if path.extension().and_then(OsStr::to_str).map(|s| s.eq_ignore_ascii_case("mp4")) != Some(true) {
+        if !is_movie_file(&path) {
             continue;
         }
         let title = strip_ext(entry.file_name().to_string_lossy().as_ref());
-        if output_already_exists(&title) {
+        let mut languages = targets.clone();
+        languages.retain(|language| !output_already_exists(&localized_name(&title, language)));
+        if languages.is_empty() {
             logi(format!("Skipping {} (already in output/)", title));
             continue;
         }
+        if opts.plan_only {
+            languages.retain(|language| !review_plan_path(&localized_name(&title, language)).exists());
+            if languages.is_empty() {
+                logi(format!("Skipping {} (plan already awaiting review in plans/)", title));
+                continue;
+            }
+        }
+        if queue.iter().any(|(_, t, _)| *t == title) {
+            logw(format!("Skipping {} (another file with title {} is already queued)", path.display(), title));
+            continue;
+        }
+        match ffmpeg::ffprobe_has_video_stream(&path).await {
+            Ok(true) => {}
+            Ok(false) => {
+                logw(format!("Skipping {} (no video stream found)", path.display()));
+                continue;
+            }
+            Err(err) => {
+                logw(format!("Skipping {} (ffprobe failed: {})", path.display(), err));
+                continue;
+            }
+        }
+        queue.push((path, title, languages));
+    }
+    queue.sort_by(|a, b| a.1.cmp(&b.1));
+
+    let parallel = cfg.concurrency.movies.max(1);
+    logi(format!("Queued {} movie(s); processing up to {} at a time.", queue.len(), parallel));
+
+    let pipe = Arc::new(pipe);
+    let slots = Arc::new(Semaphore::new(parallel));
+    let mut tasks = JoinSet::new();
+    // Kept outside the tasks so a movie whose task panics still shows up in the run report.
+    let mut spawned = HashMap::new();
+    for (order, (path, title, languages)) in queue.into_iter().enumerate() {
+        let pipe = Arc::clone(&pipe);
+        let slots = Arc::clone(&slots);
+        let plan_only = opts.plan_only;
+        let entry = (order, title.clone(), path.clone());
+        let handle = tasks.spawn(async move {
+            let _slot = slots.acquire_owned().await;
+            let mut reports: Vec<MovieReport> = Vec::new();
+            let count = languages.len();
+
+            // Languages share the source movie, so they run one after another and only the
+            // last one retires it, and only if every recap before it succeeded.
+            for (index, language) in languages.into_iter().enumerate() {
+                let name = localized_name(&title, &language);
+                let mut report = MovieReport::new(&name, &path);
+                let started = Instant::now();
+                let retire = !plan_only
+                    && index + 1 == count
+                    && reports.iter().all(|r| r.status == MovieStatus::Succeeded);
+
+                logi(format!("\n=== Processing: {} ===", name));
+                let reviewed = review_plan_path(&name);
+                let result = async {
+                    let (plan, plan_inputs) = if !plan_only && file_exists(&reviewed).await {
+                        logi(format!("Found reviewed plan: {}", reviewed.display()));
+                        let (plan, inputs) = load_plan_file(&reviewed).await?;
+                        (Some(plan), inputs)
+                    } else {
+                        (None, None)
+                    };
+                    let task = MovieTask {
+                        path: path.clone(),
+                        title: title.clone(),
+                        num_clips,
+                        fixed_clips,
+                        plan,
+                        plan_inputs,
+                        retire,
+                        plan_out: plan_only.then(|| reviewed.clone()),
+                        language,
+                    };
+                    process_movie(&pipe, &task, &mut report).await
+                }
+                .await;
+                match result {
+                    Ok(true) => {
+                        report.status = MovieStatus::Succeeded;
+                        logok(format!("DONE: {}", name));
+                    }
+                    Ok(false) => {
+                        report.status = MovieStatus::Failed;
+                        logw(format!("FAILED: {}", name));
+                    }
+                    Err(err) => {
+                        report.status = MovieStatus::Error;
+                        report.error = Some(format!("{:#}", err));
+                        logw(format!("ERROR: {}: {:#}", name, err));
+                    }
+                }
+                report.elapsed_secs = started.elapsed().as_secs_f64();
+                reports.push(report);
+            }
+            (order, reports)
+        });
+        spawned.insert(handle.id(), entry);
+    }
 
-        logi(format!("\n=== Processing: {} ===", title));
-        if process_movie(&cfg, &client, &path, &title, num_clips).await? {
-            processed += 1;
-            logok(format!("DONE: {}", title));
-        } else {
-            logw(format!("FAILED: {}", title));
+    let mut finished = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(entry) => finished.push(entry),
+            Err(err) => {
+                let Some((order, title, path)) = spawned.remove(&err.id()) else {
+                    logw(format!("Movie task panicked: {}", err));
+                    continue;
+                };
+                logw(format!("ERROR: {}: movie task panicked: {}", title, err));
+                let mut report = MovieReport::new(&title, &path);
+                report.status = MovieStatus::Error;
+                report.error = Some(format!("Movie task panicked: {}", err));
+                finished.push((order, vec![report]));
+            }
         }
     }
+    finished.sort_by_key(|(order, _)| *order);
+
+    let mut run_report = RunReport::new();
+    run_report.movies = finished.into_iter().flat_map(|(_, reports)| reports).collect();
+    run_report.log_summary();
+    match run_report.save(Path::new("reports")).await {
+        Ok(path) => logok(format!("Run report: {}", path.display())),
+        Err(err) => logw(format!("Failed to write run report: {:#}", err)),
+    }
 
+    let processed = run_report.succeeded() as i32;
     logi(format!("\nAll done. Processed: {}", processed));
     Ok(processed)
 }
 
-#[allow(dead_code)]
-fn validate_duration_range(duration: f64) -> bool {
-    let duration = duration.round() as i32;
-    duration >= MIN_TOTAL_DURATION && duration <= MAX_TOTAL_DURATION
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamp_to_seconds_reads_full_times() {
+        assert_eq!(timestamp_to_seconds("01:02:03,456"), Some(3723.456));
+        assert_eq!(timestamp_to_seconds("00:01:02.250"), Some(62.25));
+        assert_eq!(timestamp_to_seconds("00:01:02"), Some(62.0));
+    }
+
+    #[test]
+    fn timestamp_to_seconds_pads_short_fractions() {
+        assert_eq!(timestamp_to_seconds("00:01:02,5"), Some(62.5));
+        assert_eq!(timestamp_to_seconds("00:01:02,05"), Some(62.05));
+        assert_eq!(timestamp_to_seconds("00:01:02,12345"), Some(62.123));
+    }
+
+    #[test]
+    fn timestamp_to_seconds_reads_minute_times() {
+        assert_eq!(timestamp_to_seconds("01:02.500"), Some(62.5));
+        assert_eq!(timestamp_to_seconds("75:00.000"), Some(4500.0));
+    }
+
+    #[test]
+    fn timestamp_to_seconds_ignores_cue_settings() {
+        assert_eq!(timestamp_to_seconds("00:00:05,000 X1:40 X2:600 Y1:20 Y2:50"), Some(5.0));
+        assert_eq!(timestamp_to_seconds(" 00:05.000 align:start position:10%\r"), Some(5.0));
+    }
+
+    #[test]
+    fn timestamp_to_seconds_rejects_garbage() {
+        assert_eq!(timestamp_to_seconds(""), None);
+        assert_eq!(timestamp_to_seconds("5"), None);
+        assert_eq!(timestamp_to_seconds("1:2:3:4,000"), None);
+        assert_eq!(timestamp_to_seconds("00:aa:02,000"), None);
+        assert_eq!(timestamp_to_seconds("00:01:02,5x"), None);
+    }
 }
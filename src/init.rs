@@ -11,7 +11,9 @@ const REQUIRED_DIRS: &[&str] = &[
     "scripts",
     "scripts/srt_files",
     "clips",
-    "clips/audio",
+    "plans",
+    "prompts",
+    "reports",
     "resources",
 ];
 
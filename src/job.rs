@@ -0,0 +1,251 @@
+use crate::ffmpeg::CropRect;
+use crate::logw;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tokio::fs;
+
+pub const STAGE_SUBTITLES: &str = "subtitles";
+pub const STAGE_PLAN: &str = "plan";
+pub const STAGE_CONCAT: &str = "concat";
+pub const STAGE_SIDECARS: &str = "sidecars";
+pub const STAGE_BGM: &str = "bgm";
+pub const STAGE_MIX: &str = "mix";
+pub const STAGE_REFRAME: &str = "reframe";
+pub const STAGE_VERTICAL: &str = "vertical";
+
+pub fn tts_stage(clip_index: usize) -> String {
+    format!("tts_{}", clip_index)
+}
+
+pub fn render_stage(clip_index: usize) -> String {
+    format!("render_{}", clip_index)
+}
+
+/// One finished pipeline stage: the hash of everything it was built from and the files it produced.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageRecord {
+    pub input_hash: String,
+    #[serde(default)]
+    pub outputs: Vec<String>,
+    #[serde(default)]
+    pub completed_at: String,
+}
+
+/// Picture area found inside the source's black bars, for the source it was measured on. Kept
+/// per source movie, outside the work dirs, so every language and every rerun shares it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveArea {
+    /// `fingerprint_file` of the source movie.
+    pub source: String,
+    /// Detection settings the area was found with.
+    #[serde(default)]
+    pub settings: String,
+    /// `None` when the picture fills the whole frame.
+    pub crop: Option<CropRect>,
+}
+
+impl ActiveArea {
+    pub fn cache_path(movie_title: &str) -> PathBuf {
+        Path::new("scripts/srt_files").join(format!("{}_area.json", movie_title))
+    }
+
+    /// The cached area, if it was measured on the source with fingerprint `source` using `settings`.
+    pub async fn load(path: &Path, source: &str, settings: &str) -> Option<Self> {
+        let text = fs::read_to_string(path).await.ok()?;
+        let area: Self = match serde_json::from_str(&text) {
+            Ok(area) => area,
+            Err(err) => {
+                logw(format!("Active area cache {} unreadable ({}); detecting again.", path.display(), err));
+                return None;
+            }
+        };
+        (area.source == source && area.settings == settings).then_some(area)
+    }
+
+    pub async fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await.ok();
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)
+            .await
+            .with_context(|| format!("Failed to write active area: {}", path.display()))
+    }
+}
+
+/// Per-movie record of completed stages, persisted as JSON so a rerun resumes at the
+/// first stage whose inputs changed or whose artifacts went missing.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JobManifest {
+    pub movie_title: String,
+    #[serde(default)]
+    pub num_clips: Option<i32>,
+    #[serde(default)]
+    pub stages: BTreeMap<String, StageRecord>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl JobManifest {
+    /// Scratch directory holding every intermediate of one movie, so parallel jobs never collide.
+    pub fn work_dir(movie_title: &str) -> PathBuf {
+        Path::new("clips").join(movie_title)
+    }
+
+    pub fn manifest_path(movie_title: &str) -> PathBuf {
+        Self::work_dir(movie_title).join("job.json")
+    }
+
+    pub async fn load_or_new(path: &Path, movie_title: &str) -> Self {
+        let fresh = Self {
+            movie_title: movie_title.to_string(),
+            path: path.to_path_buf(),
+            ..Default::default()
+        };
+
+        let text = match fs::read_to_string(path).await {
+            Ok(text) => text,
+            Err(_) => return fresh,
+        };
+
+        match serde_json::from_str::<JobManifest>(&text) {
+            Ok(mut manifest) if manifest.movie_title == movie_title => {
+                manifest.path = path.to_path_buf();
+                manifest
+            }
+            Ok(_) => {
+                logw(format!("Job manifest {} belongs to another movie; starting fresh.", path.display()));
+                fresh
+            }
+            Err(err) => {
+                logw(format!("Job manifest {} unreadable ({}); starting fresh.", path.display(), err));
+                fresh
+            }
+        }
+    }
+
+    pub fn is_resumed(&self) -> bool {
+        !self.stages.is_empty()
+    }
+
+    /// True when `stage` finished with the same input hash and every artifact it produced still exists.
+    pub async fn is_complete(&self, stage: &str, input_hash: &str) -> bool {
+        let Some(record) = self.stages.get(stage) else {
+            return false;
+        };
+        if record.input_hash != input_hash {
+            return false;
+        }
+        for output in &record.outputs {
+            if fs::metadata(output).await.is_err() {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub async fn mark_complete(&mut self, stage: &str, input_hash: &str, outputs: &[&Path]) -> Result<()> {
+        self.stages.insert(
+            stage.to_string(),
+            StageRecord {
+                input_hash: input_hash.to_string(),
+                outputs: outputs.iter().map(|p| p.display().to_string()).collect(),
+                completed_at: chrono::Utc::now().to_rfc3339(),
+            },
+        );
+        self.save().await
+    }
+
+    pub async fn set_num_clips(&mut self, num_clips: i32) -> Result<()> {
+        self.num_clips = Some(num_clips);
+        self.save().await
+    }
+
+    pub async fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).await.ok();
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, json)
+            .await
+            .with_context(|| format!("Failed to write job manifest: {}", tmp.display()))?;
+        fs::rename(&tmp, &self.path)
+            .await
+            .with_context(|| format!("Failed to replace job manifest: {}", self.path.display()))?;
+        Ok(())
+    }
+}
+
+/// Stable 64-bit FNV-1a hasher for stage inputs; unlike `DefaultHasher` it does not change
+/// between toolchains, so manifests stay valid across rebuilds.
+#[derive(Debug, Clone)]
+pub struct InputHasher {
+    state: u64,
+}
+
+impl Default for InputHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InputHasher {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    pub fn new() -> Self {
+        Self { state: Self::OFFSET }
+    }
+
+    pub fn bytes(&mut self, data: &[u8]) -> &mut Self {
+        for b in data {
+            self.state ^= *b as u64;
+            self.state = self.state.wrapping_mul(Self::PRIME);
+        }
+        // Field separator so ("ab", "c") and ("a", "bc") differ.
+        self.state ^= 0xff;
+        self.state = self.state.wrapping_mul(Self::PRIME);
+        self
+    }
+
+    pub fn str(&mut self, s: &str) -> &mut Self {
+        self.bytes(s.as_bytes())
+    }
+
+    pub fn num(&mut self, v: impl ToString) -> &mut Self {
+        self.str(&v.to_string())
+    }
+
+    pub fn finish(&self) -> String {
+        format!("{:016x}", self.state)
+    }
+}
+
+pub fn hash_str(s: &str) -> String {
+    InputHasher::new().str(s).finish()
+}
+
+pub async fn hash_file(path: &Path) -> Result<String> {
+    let data = fs::read(path)
+        .await
+        .with_context(|| format!("Failed to read for hashing: {}", path.display()))?;
+    Ok(InputHasher::new().bytes(&data).finish())
+}
+
+/// Cheap identity for multi-gigabyte sources: size plus modification time instead of content.
+pub async fn fingerprint_file(path: &Path) -> Result<String> {
+    let meta = fs::metadata(path)
+        .await
+        .with_context(|| format!("Failed to stat: {}", path.display()))?;
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    Ok(InputHasher::new().num(meta.len()).num(mtime).finish())
+}
@@ -2,11 +2,18 @@ use once_cell::sync::Lazy;
 use std::sync::{Arc, Mutex};
 
 pub mod api;
+pub mod captions;
 pub mod clip_plan;
 pub mod config;
 pub mod ffmpeg;
 pub mod generator;
+pub mod job;
+pub mod narration;
+pub mod planner;
 pub mod platform;
+pub mod reframe;
+pub mod report;
+pub mod subtitle;
 
 pub type GeneratorLogHook = Arc<Mutex<dyn Fn(&str) + Send + Sync + 'static>>;
 
@@ -0,0 +1,188 @@
+use crate::api::elevenlabs::ElevenLabsSynth;
+use crate::captions::WordTiming;
+use crate::config::{self, Config, LanguageProfile, LocalTtsConfig, LocalTtsEngine, TtsBackend};
+use crate::ffmpeg;
+use crate::logw;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::Arc;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// A text-to-speech backend that renders one narration line to an mp3 file.
+#[async_trait]
+pub trait NarrationSynth: Send + Sync {
+    fn describe(&self) -> String;
+
+    /// Everything that changes the rendered audio besides the text (voice, model, engine, ...),
+    /// used to decide whether cached narration can be reused.
+    fn cache_key(&self) -> String;
+
+    async fn synthesize(&self, text: &str, out_mp3_path: &Path) -> Result<bool>;
+
+    /// Like `synthesize`, also reporting when each word is spoken. `Ok(None)` is a failed
+    /// synthesis; an empty list means the backend has no timings and callers should estimate them.
+    async fn synthesize_timed(&self, text: &str, out_mp3_path: &Path) -> Result<Option<Vec<WordTiming>>> {
+        Ok(self.synthesize(text, out_mp3_path).await?.then(Vec::new))
+    }
+
+    /// The same backend speaking with another voice, if it supports switching by voice id.
+    fn with_voice(&self, _voice_id: &str) -> Option<Arc<dyn NarrationSynth>> {
+        None
+    }
+
+    /// The same backend set up to speak `code` (e.g. `es`), if it can.
+    fn with_language(&self, _code: &str, _profile: &LanguageProfile) -> Option<Arc<dyn NarrationSynth>> {
+        None
+    }
+}
+
+pub fn make_synth(cfg: &Config, client: &Client) -> Arc<dyn NarrationSynth> {
+    match cfg.tts.backend {
+        TtsBackend::Elevenlabs => Arc::new(ElevenLabsSynth::new(client.clone(), cfg)),
+        TtsBackend::Local => Arc::new(LocalEngineSynth::new(&cfg.tts.local)),
+    }
+}
+
+/// Offline narration through a local engine (piper or espeak-ng), transcoded to mp3.
+#[derive(Clone)]
+pub struct LocalEngineSynth {
+    engine: LocalTtsEngine,
+    binary: String,
+    model: String,
+    voice: String,
+    rate_wpm: u32,
+}
+
+impl LocalEngineSynth {
+    pub fn new(lc: &LocalTtsConfig) -> Self {
+        let binary = if lc.binary.is_empty() {
+            match lc.engine {
+                LocalTtsEngine::Piper => "piper",
+                LocalTtsEngine::EspeakNg => "espeak-ng",
+            }
+            .to_string()
+        } else {
+            lc.binary.clone()
+        };
+        Self {
+            engine: lc.engine,
+            binary,
+            model: lc.model.clone(),
+            voice: lc.voice.clone(),
+            rate_wpm: lc.rate_wpm,
+        }
+    }
+
+    async fn render_wav(&self, text: &str, wav_path: &Path) -> Result<bool> {
+        let mut cmd = Command::new(&self.binary);
+        match self.engine {
+            LocalTtsEngine::Piper => {
+                if self.model.is_empty() {
+                    logw("piper needs tts.local.model (path to an .onnx voice).");
+                    return Ok(false);
+                }
+                cmd.arg("--model").arg(&self.model).arg("--output_file").arg(wav_path);
+            }
+            LocalTtsEngine::EspeakNg => {
+                cmd.arg("-v")
+                    .arg(&self.voice)
+                    .arg("-s")
+                    .arg(self.rate_wpm.to_string())
+                    .arg("-w")
+                    .arg(wav_path)
+                    .arg("--stdin");
+            }
+        }
+
+        let mut child = match cmd
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(err) => {
+                logw(format!("Failed to start local TTS engine {}: {}", self.binary, err));
+                return Ok(false);
+            }
+        };
+
+        if let Some(mut stdin) = child.stdin.take() {
+            let written = match stdin.write_all(text.as_bytes()).await {
+                Ok(()) => stdin.write_all(b"\n").await,
+                Err(err) => Err(err),
+            };
+            if let Err(err) = written {
+                // The engine quit before reading the text (bad model path, unknown voice); its
+                // stderr says why.
+                drop(stdin);
+                let stderr = match child.wait_with_output().await {
+                    Ok(output) => String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                    Err(_) => String::new(),
+                };
+                logw(format!("{} stopped reading the narration ({}): {}", self.binary, err, stderr));
+                return Ok(false);
+            }
+        }
+
+        let output = child.wait_with_output().await?;
+        if !output.status.success() {
+            let err = String::from_utf8_lossy(&output.stderr);
+            logw(format!("{} failed: {}", self.binary, err.trim()));
+            return Ok(false);
+        }
+        Ok(fs::metadata(wav_path).await.is_ok())
+    }
+}
+
+#[async_trait]
+impl NarrationSynth for LocalEngineSynth {
+    fn describe(&self) -> String {
+        format!("local TTS ({})", self.binary)
+    }
+
+    fn cache_key(&self) -> String {
+        format!("local:{:?}:{}:{}:{}", self.engine, self.model, self.voice, self.rate_wpm)
+    }
+
+    async fn synthesize(&self, text: &str, out_mp3_path: &Path) -> Result<bool> {
+        if let Some(parent) = out_mp3_path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("Failed to create dir {}", parent.display()))?;
+        }
+
+        let wav_path = out_mp3_path.with_extension("wav");
+        let ok = self.render_wav(text, &wav_path).await?
+            && ffmpeg::ffmpeg_wav_to_mp3(&wav_path, out_mp3_path).await?;
+        let _ = fs::remove_file(&wav_path).await;
+        Ok(ok)
+    }
+
+    fn with_language(&self, code: &str, profile: &LanguageProfile) -> Option<Arc<dyn NarrationSynth>> {
+        let mut synth = self.clone();
+        match self.engine {
+            // espeak-ng voices are named after language codes.
+            LocalTtsEngine::EspeakNg => {
+                if !profile.voice_id.is_empty() {
+                    synth.voice = profile.voice_id.clone();
+                } else if !config::is_english(code) {
+                    synth.voice = code.to_string();
+                }
+            }
+            // A piper model speaks one language; without a model for `code` there is nothing to switch to.
+            LocalTtsEngine::Piper => {
+                if profile.model_id.is_empty() {
+                    return None;
+                }
+                synth.model = profile.model_id.clone();
+            }
+        }
+        Some(Arc::new(synth))
+    }
+}
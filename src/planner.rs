@@ -0,0 +1,450 @@
+use crate::api::{chat_completions::ChatCompletionsPlanner, openai::OpenAiResponsesPlanner};
+use crate::clip_plan::{ClipPlanList, MAX_CLIP_DURATION, MAX_TOTAL_DURATION, MIN_CLIP_DURATION, MIN_TOTAL_DURATION};
+use crate::config::{Config, PlannerBackend};
+use crate::logw;
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+pub const MAX_SUB_CHARS: usize = 320_000;
+const MAX_SCRIPT_CHARS: usize = 80_000;
+
+/// Inputs for one clip-planning request.
+#[derive(Debug, Clone, Copy)]
+pub struct PlanRequest<'a> {
+    pub movie_title: &'a str,
+    pub subs_seconds_text: &'a str,
+    pub script_text: &'a str,
+    pub num_clips: i32,
+    /// Set on follow-up requests that ask the model to correct its previous plan.
+    pub feedback: Option<&'a PlanFeedback>,
+    /// `subs_seconds_text` holds condensed beats (see [`format_beats`]) rather than raw subtitles.
+    pub beats: bool,
+    /// Prompt template with `{{name}}` placeholders; empty selects [`BUILTIN_PLAN_TEMPLATE`].
+    pub template: &'a str,
+    /// Required start of the first narration (may use `{{title}}`); empty disables the check.
+    pub opening_line: &'a str,
+    pub style_instructions: &'a str,
+    pub min_words: u32,
+    pub max_words: u32,
+    /// Language the narration is written in (e.g. `Spanish`).
+    pub language: &'a str,
+}
+
+/// One window of subtitles to condense into beats.
+#[derive(Debug, Clone, Copy)]
+pub struct BeatRequest<'a> {
+    pub movie_title: &'a str,
+    pub window_text: &'a str,
+    pub index: usize,
+    pub total: usize,
+}
+
+/// A summarised stretch of the movie, used in place of raw subtitles for long films.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Beat {
+    pub start: f64,
+    pub end: f64,
+    pub summary: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BeatRoot {
+    beats: Vec<Beat>,
+}
+
+/// What a backend got back from one request: the model text, or a logged soft failure.
+pub(crate) enum PlannerReply {
+    Text(String),
+    Failed { retry_without_script: bool },
+}
+
+/// A rejected plan and what was wrong with it, sent back to the model.
+#[derive(Debug, Clone)]
+pub struct PlanFeedback {
+    pub previous_json: String,
+    pub violations: Vec<String>,
+}
+
+pub const DEFAULT_OPENING_LINE: &str = "Here we go, let's go over the movie {{title}}.";
+
+/// Placeholders a plan template may use.
+pub const PLAN_TEMPLATE_VARS: &[&str] = &[
+    "title",
+    "subs_label",
+    "subs",
+    "script",
+    "num_clips",
+    "min_clip_secs",
+    "max_clip_secs",
+    "min_total_secs",
+    "max_total_secs",
+    "opening_line",
+    "style_instructions",
+    "min_words",
+    "max_words",
+    "language",
+];
+
+/// The planning prompt used when no template file is selected.
+pub const BUILTIN_PLAN_TEMPLATE: &str = "You are given TWO inputs.\nMovie: {{title}}\n\n{{subs_label}}:\n{{subs}}\n\nINPUT B (Optional script text WITHOUT timestamps; may be empty):\n{{script}}\n\nTASK:\n- Choose {{num_clips}} non-overlapping time ranges that best cover the full plot arc.\n- ONLY use INPUT A for selecting start/end times (seconds). INPUT B is for story context.\n- Keep the fractional part of the timestamps (e.g. 120.480) so cuts land exactly on subtitle lines.\n- Each time range should be at least {{min_clip_secs}} seconds long (end-start). Avoid >{{max_clip_secs}} seconds.\n- All ranges together should add up to {{min_total_secs}}-{{max_total_secs}} seconds.\n- Keep narrations punchy but not tiny: about {{min_words}}-{{max_words}} words total, in 3-5 short sentences.\n- Prefer ranges with clear visual action (reveals, confrontations, entrances, big moments).\n- Skip any range that starts at 0.\n- Return STRICT JSON with this shape ONLY:\n  {\"clips\":[{\"start\":120.48,\"end\":145.2,\"narration\":\"...\"}, ...]}\n- Clips must be increasing by start time.\n- Each narration must be at least 3 full sentences, {{style_instructions}}.\n- Write every narration in {{language}}.\n- The first narration must start with: \"{{opening_line}}\".\n";
+
+/// Replaces each `{{name}}` with its value; unknown placeholders are left untouched.
+pub fn render_template(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find("{{") {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 2..];
+        let Some(close) = after.find("}}") else {
+            out.push_str(&rest[open..]);
+            return out;
+        };
+        let name = after[..close].trim();
+        match vars.iter().find(|(k, _)| *k == name) {
+            Some((_, value)) => out.push_str(value),
+            None => out.push_str(&rest[open..open + 2 + close + 2]),
+        }
+        rest = &after[close + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Placeholder names in `template` that are not in `known`.
+pub fn unknown_placeholders(template: &str, known: &[&str]) -> Vec<String> {
+    let mut unknown = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find("{{") {
+        let after = &rest[open + 2..];
+        let Some(close) = after.find("}}") else {
+            break;
+        };
+        let name = after[..close].trim();
+        if !known.contains(&name) && !unknown.iter().any(|u| u == name) {
+            unknown.push(name.to_string());
+        }
+        rest = &after[close + 2..];
+    }
+    unknown
+}
+
+/// The required opening of the first narration with `{{title}}` filled in; empty when unchecked.
+pub fn opening_line(req: &PlanRequest<'_>) -> String {
+    render_template(req.opening_line, &[("title", req.movie_title)])
+}
+
+/// Rules from the prompt the plan breaks, including everything the validator would have to repair.
+pub fn plan_violations(plan: &ClipPlanList, req: &PlanRequest<'_>, movie_duration: Option<f64>) -> Vec<String> {
+    let mut repaired = plan.clone();
+    let validation = repaired.validate_and_repair(movie_duration);
+    let mut violations: Vec<String> = validation
+        .fixes
+        .into_iter()
+        .map(|fix| format!("needed repair: {}", fix))
+        .chain(validation.problems)
+        .collect();
+
+    let wanted = req.num_clips.max(0) as usize;
+    if repaired.items.len() < wanted {
+        violations.push(format!(
+            "only {} usable clips; {} were requested",
+            repaired.items.len(),
+            wanted
+        ));
+    }
+
+    let opening = opening_line(req);
+    if !opening.trim().is_empty() {
+        let starts_right = plan
+            .items
+            .first()
+            .map(|c| c.narration.trim().replace('\u{2019}', "'").to_lowercase().starts_with(&opening.to_lowercase()))
+            .unwrap_or(false);
+        if !starts_right {
+            violations.push(format!("the first narration does not start with \"{}\"", opening));
+        }
+    }
+    violations
+}
+
+/// Planner answers that arrived but cannot be used as a clip plan.
+#[derive(Debug, thiserror::Error)]
+pub enum PlannerError {
+    #[error("planner refused the request: {0}")]
+    Refused(String),
+    #[error("planner output was cut off: {0}")]
+    Incomplete(String),
+    #[error("planner output is not valid JSON: {message}")]
+    InvalidJson { message: String, output: String },
+    #[error("planner output does not match the clip plan schema: {message}")]
+    SchemaViolation { message: String, output: String },
+}
+
+impl PlannerError {
+    /// The raw model output, when there is one worth sending back for correction.
+    pub fn output(&self) -> Option<&str> {
+        match self {
+            PlannerError::InvalidJson { output, .. } | PlannerError::SchemaViolation { output, .. } => Some(output),
+            PlannerError::Refused(_) | PlannerError::Incomplete(_) => None,
+        }
+    }
+}
+
+/// Checks model output against [`ClipPlanList::json_schema`] and converts it, naming the first
+/// offending field instead of failing somewhere inside serde.
+pub fn parse_plan_output(text: &str) -> std::result::Result<ClipPlanList, PlannerError> {
+    let root: Value = serde_json::from_str(text).map_err(|err| PlannerError::InvalidJson {
+        message: err.to_string(),
+        output: text.to_string(),
+    })?;
+    let violation = |message: String| PlannerError::SchemaViolation {
+        message,
+        output: text.to_string(),
+    };
+
+    let obj = root.as_object().ok_or_else(|| violation("top level must be an object".to_string()))?;
+    if let Some(extra) = obj.keys().find(|k| *k != "clips") {
+        return Err(violation(format!("unexpected top-level field \"{}\"", extra)));
+    }
+    let clips = obj
+        .get("clips")
+        .ok_or_else(|| violation("missing \"clips\"".to_string()))?
+        .as_array()
+        .ok_or_else(|| violation("\"clips\" must be an array".to_string()))?;
+
+    for (idx, clip) in clips.iter().enumerate() {
+        let clip = clip
+            .as_object()
+            .ok_or_else(|| violation(format!("clips[{}] must be an object", idx)))?;
+        for (field, is_valid, expected) in [
+            ("start", Value::is_number as fn(&Value) -> bool, "a number"),
+            ("end", Value::is_number, "a number"),
+            ("narration", Value::is_string, "a string"),
+        ] {
+            match clip.get(field) {
+                None => return Err(violation(format!("clips[{}].{} is missing", idx, field))),
+                Some(v) if !is_valid(v) => {
+                    return Err(violation(format!("clips[{}].{} must be {}, got {}", idx, field, expected, v)));
+                }
+                Some(_) => {}
+            }
+        }
+        if let Some(extra) = clip.keys().find(|k| !["start", "end", "narration"].contains(&k.as_str())) {
+            return Err(violation(format!("clips[{}] has unexpected field \"{}\"", idx, extra)));
+        }
+    }
+
+    ClipPlanList::from_json(text).map_err(|err| violation(format!("{:#}", err)))
+}
+
+/// A backend that turns subtitles (and optional script context) into a clip plan.
+///
+/// `make_plan` returns an empty plan on soft failures, together with a flag telling the
+/// caller whether retrying without the script context is likely to help. Answers that arrive
+/// but are unusable come back as a [`PlannerError`] inside the `anyhow::Error`.
+#[async_trait]
+pub trait ClipPlanner: Send + Sync {
+    fn describe(&self) -> String;
+
+    async fn make_plan(&self, req: &PlanRequest<'_>) -> Result<(ClipPlanList, bool)>;
+
+    /// Condenses one subtitle window into beats; an empty list is a soft failure.
+    async fn summarize_window(&self, req: &BeatRequest<'_>) -> Result<Vec<Beat>>;
+}
+
+pub fn make_planner(cfg: &Config, client: &Client) -> Box<dyn ClipPlanner> {
+    let pc = &cfg.planner;
+    let api_key = cfg.planner_api_key().to_string();
+    match pc.backend {
+        PlannerBackend::OpenaiResponses => Box::new(OpenAiResponsesPlanner::new(
+            client.clone(),
+            &pc.base_url,
+            &pc.model,
+            &api_key,
+        )),
+        PlannerBackend::ChatCompletions => Box::new(ChatCompletionsPlanner::new(
+            client.clone(),
+            &pc.base_url,
+            &pc.model,
+            &api_key,
+        )),
+    }
+}
+
+pub(crate) const PLAN_SYSTEM_PROMPT: &str = "You are a helpful assistant designed to output JSON.";
+
+/// Planning can take a long time on big inputs; scripts roughly quadruple the prompt.
+pub(crate) fn plan_timeout(req: &PlanRequest<'_>) -> std::time::Duration {
+    let timeout_s = if req.script_text.is_empty() { 3_600 } else { 14_400 };
+    std::time::Duration::from_secs(timeout_s)
+}
+
+fn sanitize_utf8_lossy(input: &str) -> String {
+    String::from_utf8_lossy(input.as_bytes()).into_owned()
+}
+
+fn trim_copy_utf8_safe(input: &str, max_bytes: usize) -> String {
+    if input.len() <= max_bytes {
+        return input.to_string();
+    }
+
+    let mut cut = max_bytes.min(input.len());
+    while cut > 0 && !input.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    input[..cut].to_string()
+}
+
+pub(crate) const BEAT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1_800);
+
+/// Splits converted subtitles into windows of at most `max_chars`, only at cue boundaries
+/// (blank lines), so no cue is cut in half.
+pub fn split_subtitle_windows(subs: &str, max_chars: usize) -> Vec<&str> {
+    let max_chars = max_chars.max(1);
+    let mut windows = Vec::new();
+    let mut start = 0;
+    let mut last_break = 0;
+    let mut pos = 0;
+
+    for line in subs.split_inclusive('\n') {
+        pos += line.len();
+        if line.trim().is_empty() {
+            last_break = pos;
+        }
+        if pos - start > max_chars && last_break > start {
+            windows.push(&subs[start..last_break]);
+            start = last_break;
+        }
+    }
+    if start < subs.len() && !subs[start..].trim().is_empty() {
+        windows.push(&subs[start..]);
+    }
+    windows
+}
+
+/// Renders beats in the same `start --> end` layout as the converted subtitles.
+pub fn format_beats(beats: &[Beat]) -> String {
+    let mut out = String::new();
+    for (idx, beat) in beats.iter().enumerate() {
+        out.push_str(&format!(
+            "{}\n{:.3} --> {:.3}\n{}\n\n",
+            idx + 1,
+            beat.start,
+            beat.end,
+            beat.summary.trim()
+        ));
+    }
+    out
+}
+
+pub(crate) fn beat_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "beats": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "start": {"type": "number"},
+                        "end": {"type": "number"},
+                        "summary": {"type": "string"},
+                    },
+                    "required": ["start", "end", "summary"],
+                    "additionalProperties": false,
+                },
+            },
+        },
+        "required": ["beats"],
+        "additionalProperties": false,
+    })
+}
+
+pub(crate) fn parse_beats_output(text: &str) -> std::result::Result<Vec<Beat>, PlannerError> {
+    let root: Value = serde_json::from_str(text).map_err(|err| PlannerError::InvalidJson {
+        message: err.to_string(),
+        output: text.to_string(),
+    })?;
+    let root: BeatRoot = serde_json::from_value(root).map_err(|err| PlannerError::SchemaViolation {
+        message: err.to_string(),
+        output: text.to_string(),
+    })?;
+    Ok(root.beats)
+}
+
+pub(crate) fn build_beat_prompt(req: &BeatRequest<'_>) -> String {
+    format!(
+        "Movie: {}\n\nBelow is part {} of {} of the movie's subtitles. Timestamps are in SECONDS.\n\nSUBTITLES:\n{}\n\nTASK:\n- Condense this part into a list of story beats covering it from start to end, without gaps.\n- Each beat spans a contiguous stretch of roughly 30-120 seconds; use start/end times taken from the subtitles, keeping fractions.\n- Each summary is 1-2 sentences on what happens and who is involved, naming characters.\n- Call out visually striking moments (reveals, fights, chases, big entrances) with their exact times.\n- Return STRICT JSON with this shape ONLY:\n  {{\"beats\":[{{\"start\":120.48,\"end\":190.2,\"summary\":\"...\"}}, ...]}}\n",
+        sanitize_utf8_lossy(req.movie_title),
+        req.index + 1,
+        req.total,
+        sanitize_utf8_lossy(req.window_text)
+    )
+}
+
+pub(crate) fn build_plan_prompt(req: &PlanRequest<'_>) -> String {
+    let title_utf8 = sanitize_utf8_lossy(req.movie_title);
+    let subs_utf8 = sanitize_utf8_lossy(req.subs_seconds_text);
+    let script_utf8 = sanitize_utf8_lossy(req.script_text);
+
+    let subs_trim = trim_copy_utf8_safe(&subs_utf8, MAX_SUB_CHARS);
+    let script_trim = trim_copy_utf8_safe(&script_utf8, MAX_SCRIPT_CHARS);
+    if script_trim.len() < script_utf8.len() {
+        logw(format!(
+            "Script for {} truncated from {} to {} bytes for the planning prompt",
+            title_utf8,
+            script_utf8.len(),
+            script_trim.len()
+        ));
+    }
+
+    let input_a = if req.beats {
+        "INPUT A (Timestamped story beats condensed from the full subtitles; times in SECONDS)"
+    } else {
+        "INPUT A (Subtitles with timestamps in SECONDS, millisecond precision)"
+    };
+
+    let opening = opening_line(req);
+    let min_clip = format!("{}", MIN_CLIP_DURATION);
+    let max_clip = format!("{}", MAX_CLIP_DURATION);
+    let min_total = format!("{}", MIN_TOTAL_DURATION);
+    let max_total = format!("{}", MAX_TOTAL_DURATION);
+    let num_clips = req.num_clips.to_string();
+    let min_words = req.min_words.to_string();
+    let max_words = req.max_words.to_string();
+    let template = if req.template.trim().is_empty() { BUILTIN_PLAN_TEMPLATE } else { req.template };
+
+    let mut prompt = render_template(
+        template,
+        &[
+            ("title", &title_utf8),
+            ("subs_label", input_a),
+            ("subs", &subs_trim),
+            ("script", &script_trim),
+            ("num_clips", &num_clips),
+            ("min_clip_secs", &min_clip),
+            ("max_clip_secs", &max_clip),
+            ("min_total_secs", &min_total),
+            ("max_total_secs", &max_total),
+            ("opening_line", &opening),
+            ("style_instructions", req.style_instructions),
+            ("min_words", &min_words),
+            ("max_words", &max_words),
+            ("language", req.language),
+        ],
+    );
+
+    if let Some(feedback) = req.feedback {
+        prompt.push_str("\nYOUR PREVIOUS ANSWER:\n");
+        prompt.push_str(&feedback.previous_json);
+        prompt.push_str("\n\nIt broke these rules:\n");
+        for violation in &feedback.violations {
+            prompt.push_str(&format!("- {}\n", violation));
+        }
+        prompt.push_str("\nReturn a corrected plan in the same JSON shape that fixes every item above and still follows the TASK rules.\n");
+    }
+    prompt
+}
@@ -0,0 +1,278 @@
+use crate::config::VerticalConfig;
+use crate::ffmpeg::{self, CropRect};
+use crate::logw;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::fs;
+
+/// Mean absolute difference between consecutive samples (0-1) that counts as a cut.
+pub const CUT_THRESHOLD: f64 = 0.2;
+
+/// Share of a column's saliency taken from motion; the rest comes from edge energy.
+const MOTION_WEIGHT: f64 = 0.6;
+
+/// How much a window at the frame edge is penalised against one in the middle, so shots
+/// without a clear subject stay centred.
+const CENTRE_BIAS: f64 = 0.25;
+
+/// Crop commands per second sent to the render; the path is interpolated between samples.
+const COMMAND_RATE: f64 = 15.0;
+
+/// Centre of the crop window at one analysed frame, as a fraction of the frame width.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CropPoint {
+    pub t: f64,
+    pub center: f64,
+    /// First sample after a cut; the crop jumps here instead of panning.
+    #[serde(default)]
+    pub cut: bool,
+}
+
+/// Mean absolute difference of two equally sized grayscale frames, 0 (same) to 1.
+pub fn frame_difference(a: &[u8], b: &[u8]) -> f64 {
+    if a.is_empty() || a.len() != b.len() {
+        return 0.0;
+    }
+    let total: u64 = a.iter().zip(b).map(|(x, y)| x.abs_diff(*y) as u64).sum();
+    total as f64 / (a.len() as f64 * 255.0)
+}
+
+/// Per-column saliency: horizontal + vertical gradients, plus the change since `prev` when given.
+/// Each part is normalised to sum to 1 so neither dominates because of its scale.
+fn column_saliency(frame: &[u8], prev: Option<&[u8]>, width: usize, height: usize) -> Vec<f64> {
+    let px = |y: usize, x: usize| frame[y * width + x] as i32;
+    let mut edges = vec![0.0; width];
+    for y in 0..height.saturating_sub(1) {
+        for (x, e) in edges.iter_mut().enumerate().take(width.saturating_sub(1)) {
+            let here = px(y, x);
+            *e += ((px(y, x + 1) - here).abs() + (px(y + 1, x) - here).abs()) as f64;
+        }
+    }
+    normalise(&mut edges);
+
+    let Some(prev) = prev else {
+        return edges;
+    };
+    let mut motion = vec![0.0; width];
+    for y in 0..height {
+        for (x, m) in motion.iter_mut().enumerate() {
+            *m += frame[y * width + x].abs_diff(prev[y * width + x]) as f64;
+        }
+    }
+    if !normalise(&mut motion) {
+        return edges;
+    }
+    edges
+        .iter()
+        .zip(&motion)
+        .map(|(e, m)| (1.0 - MOTION_WEIGHT) * e + MOTION_WEIGHT * m)
+        .collect()
+}
+
+/// Scales `values` to sum to 1; false (values untouched) when they are all zero.
+fn normalise(values: &mut [f64]) -> bool {
+    let sum: f64 = values.iter().sum();
+    if sum <= 0.0 {
+        return false;
+    }
+    values.iter_mut().for_each(|v| *v /= sum);
+    true
+}
+
+/// Centre (fraction of the width) of the `window`-column span holding the most saliency.
+fn best_window_center(profile: &[f64], window: usize) -> f64 {
+    let width = profile.len();
+    if width == 0 || window >= width {
+        return 0.5;
+    }
+    let mut prefix = vec![0.0; width + 1];
+    for (i, v) in profile.iter().enumerate() {
+        prefix[i + 1] = prefix[i] + v;
+    }
+    // A blank frame has no subject to follow; without this every window ties and the first wins.
+    if prefix[width] <= 0.0 {
+        return 0.5;
+    }
+
+    let mut best = (f64::MIN, 0.5);
+    for start in 0..=width - window {
+        let center = (start as f64 + window as f64 / 2.0) / width as f64;
+        let bias = 1.0 - CENTRE_BIAS * (center - 0.5).abs() * 2.0;
+        let score = (prefix[start + window] - prefix[start]) * bias;
+        if score > best.0 {
+            best = (score, center);
+        }
+    }
+    best.1
+}
+
+/// Moving average over `smoothing_secs`, then a pan-speed limit, within one shot.
+fn smooth_shot(raw: &[f64], fps: f64, cfg: &VerticalConfig) -> Vec<f64> {
+    let half = ((cfg.smoothing_secs.max(0.0) * fps) / 2.0).round() as usize;
+    let averaged: Vec<f64> = (0..raw.len())
+        .map(|i| {
+            let lo = i.saturating_sub(half);
+            let hi = (i + half + 1).min(raw.len());
+            raw[lo..hi].iter().sum::<f64>() / (hi - lo) as f64
+        })
+        .collect();
+
+    let max_step = cfg.max_pan_speed.max(0.0) / fps;
+    let mut out: Vec<f64> = Vec::with_capacity(averaged.len());
+    for &target in &averaged {
+        let next = match out.last() {
+            Some(&prev) => prev + (target - prev).clamp(-max_step, max_step),
+            None => target,
+        };
+        out.push(next);
+    }
+    out
+}
+
+/// Samples `video` (the `area` part of it, when given), scores each frame's columns by motion
+/// and edge energy, and returns a smoothed path for the crop window of `cfg.layout`. Smoothing
+/// restarts at every cut, so the crop jumps between shots (and clips) but pans within them.
+pub async fn analyze_crop_path(video: &Path, area: Option<&CropRect>, cfg: &VerticalConfig) -> Result<Vec<CropPoint>> {
+    let (w, h) = match area {
+        Some(area) => (area.w, area.h),
+        None => ffmpeg::ffprobe_video_dimensions(video).await?,
+    };
+    let crop_fraction = ffmpeg::vertical_crop_fraction(cfg.layout, w, h);
+    let width = cfg.analysis_width.max(32) as usize;
+    let height = ((width as f64 * h as f64 / w as f64 / 2.0).round() as usize * 2).max(2);
+    let fps = cfg.analysis_fps.clamp(1.0, 30.0);
+    let window = (width as f64 * crop_fraction).round() as usize;
+
+    let mut shots: Vec<Vec<f64>> = Vec::new();
+    let mut prev: Option<Vec<u8>> = None;
+    ffmpeg::ffmpeg_gray_frames(video, area, fps, width, height, |frame| {
+        let same_shot = prev
+            .as_deref()
+            .is_some_and(|p| frame_difference(frame, p) < CUT_THRESHOLD);
+        if !same_shot {
+            shots.push(Vec::new());
+        }
+        let profile = column_saliency(frame, prev.as_deref().filter(|_| same_shot), width, height);
+        if let Some(shot) = shots.last_mut() {
+            shot.push(best_window_center(&profile, window));
+        }
+        prev = Some(frame.to_vec());
+    })
+    .await?;
+
+    let mut points = Vec::new();
+    for shot in &shots {
+        for (k, center) in smooth_shot(shot, fps, cfg).into_iter().enumerate() {
+            points.push(CropPoint {
+                t: points.len() as f64 / fps,
+                center,
+                cut: k == 0,
+            });
+        }
+    }
+    Ok(points)
+}
+
+pub async fn load_path(path: &Path) -> Option<Vec<CropPoint>> {
+    let text = fs::read_to_string(path).await.ok()?;
+    match serde_json::from_str(&text) {
+        Ok(points) => Some(points),
+        Err(err) => {
+            logw(format!("Reframing path unreadable ({}): {}", err, path.display()));
+            None
+        }
+    }
+}
+
+/// Expands analysed points into `(seconds, centre)` crop commands: interpolated within a shot,
+/// and switched halfway between the samples either side of a cut.
+pub fn command_points(points: &[CropPoint]) -> Vec<(f64, f64)> {
+    let step = 1.0 / COMMAND_RATE;
+    let mut out = Vec::new();
+    for (i, point) in points.iter().enumerate() {
+        if point.cut && i > 0 {
+            out.push(((points[i - 1].t + point.t) / 2.0, point.center));
+        }
+        let next = match points.get(i + 1) {
+            Some(next) if !next.cut => next,
+            _ => {
+                out.push((point.t, point.center));
+                continue;
+            }
+        };
+        let span = next.t - point.t;
+        let mut t = point.t;
+        while t < next.t {
+            let along = if span > 0.0 { (t - point.t) / span } else { 0.0 };
+            out.push((t, point.center + (next.center - point.center) * along));
+            t += step;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(t: f64, center: f64, cut: bool) -> CropPoint {
+        CropPoint { t, center, cut }
+    }
+
+    #[test]
+    fn best_window_follows_the_salient_columns() {
+        let mut profile = vec![0.0; 10];
+        profile[8] = 1.0;
+        assert!((best_window_center(&profile, 4) - 0.7).abs() < 1e-9);
+    }
+
+    #[test]
+    fn best_window_stays_centred_without_a_subject() {
+        assert_eq!(best_window_center(&[0.1; 10], 4), 0.5);
+        assert_eq!(best_window_center(&[0.0; 10], 4), 0.5);
+        assert_eq!(best_window_center(&[1.0; 4], 4), 0.5);
+        assert_eq!(best_window_center(&[], 4), 0.5);
+    }
+
+    #[test]
+    fn smooth_shot_limits_the_pan_speed() {
+        let cfg = VerticalConfig {
+            smoothing_secs: 1.0,
+            max_pan_speed: 0.3,
+            ..VerticalConfig::default()
+        };
+        let fps = 4.0;
+        let raw: Vec<f64> = [[0.2; 8], [0.8; 8]].concat();
+        let out = smooth_shot(&raw, fps, &cfg);
+        assert_eq!(out.len(), raw.len());
+        assert!((out[0] - 0.2).abs() < 1e-9);
+        assert!(out.windows(2).all(|w| w[1] - w[0] > -1e-9 && w[1] - w[0] <= 0.3 / fps + 1e-9));
+        assert!(out[15] > 0.5);
+
+        assert!(smooth_shot(&[0.4; 6], fps, &cfg).iter().all(|c| (c - 0.4).abs() < 1e-9));
+        assert!(smooth_shot(&[], fps, &cfg).is_empty());
+    }
+
+    #[test]
+    fn command_points_pan_within_shots_and_jump_at_cuts() {
+        let points = [
+            point(0.0, 0.3, true),
+            point(0.5, 0.5, false),
+            point(1.0, 0.9, true),
+            point(1.5, 0.9, false),
+        ];
+        let commands = command_points(&points);
+        assert_eq!(commands.first(), Some(&(0.0, 0.3)));
+        assert_eq!(commands.last(), Some(&(1.5, 0.9)));
+        assert!(commands.windows(2).all(|w| w[1].0 >= w[0].0));
+        assert!(commands.contains(&(0.75, 0.9)));
+        for &(t, center) in &commands {
+            if t < 0.75 {
+                assert!((0.3..=0.5).contains(&center), "{} at {}", center, t);
+            } else {
+                assert_eq!(center, 0.9, "at {}", t);
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use crate::subtitle::SubtitleAttempt;
+use crate::{logi, logok, logw};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MovieStatus {
+    Succeeded,
+    /// The pipeline gave up on the movie (no subtitles, no plan, no clips, ...).
+    Failed,
+    /// An unexpected error aborted the movie.
+    Error,
+}
+
+/// One planner request and the rule violations found in its answer.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanAttempt {
+    pub attempt: u32,
+    pub clips: usize,
+    pub violations: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MovieReport {
+    pub title: String,
+    pub source: String,
+    pub status: MovieStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub elapsed_secs: f64,
+    /// Subtitle providers tried for the movie, in order, when it had no SRT yet.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub subtitle_attempts: Vec<SubtitleAttempt>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub plan_attempts: Vec<PlanAttempt>,
+}
+
+impl MovieReport {
+    pub fn new(title: &str, source: &Path) -> Self {
+        Self {
+            title: title.to_string(),
+            source: source.display().to_string(),
+            status: MovieStatus::Failed,
+            error: None,
+            elapsed_secs: 0.0,
+            subtitle_attempts: Vec::new(),
+            plan_attempts: Vec::new(),
+        }
+    }
+}
+
+/// Outcome of one `run_generation` call, written to `reports/` when the queue drains.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunReport {
+    pub started_at: String,
+    pub finished_at: String,
+    pub movies: Vec<MovieReport>,
+}
+
+impl Default for RunReport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RunReport {
+    pub fn new() -> Self {
+        Self {
+            started_at: chrono::Local::now().to_rfc3339(),
+            finished_at: String::new(),
+            movies: Vec::new(),
+        }
+    }
+
+    pub fn succeeded(&self) -> usize {
+        self.movies
+            .iter()
+            .filter(|m| m.status == MovieStatus::Succeeded)
+            .count()
+    }
+
+    pub fn log_summary(&self) {
+        logi(format!(
+            "\n=== Summary: {} succeeded, {} failed ===",
+            self.succeeded(),
+            self.movies.len() - self.succeeded()
+        ));
+        for movie in &self.movies {
+            match movie.status {
+                MovieStatus::Succeeded => logok(format!("  {} ({:.0}s)", movie.title, movie.elapsed_secs)),
+                MovieStatus::Failed => logw(format!("  FAILED {} ({:.0}s)", movie.title, movie.elapsed_secs)),
+                MovieStatus::Error => logw(format!(
+                    "  ERROR {}: {}",
+                    movie.title,
+                    movie.error.as_deref().unwrap_or("unknown error")
+                )),
+            }
+        }
+    }
+
+    pub async fn save(&mut self, dir: &Path) -> Result<PathBuf> {
+        self.finished_at = chrono::Local::now().to_rfc3339();
+        fs::create_dir_all(dir).await.ok();
+        let name = format!("run_{}.json", chrono::Local::now().format("%Y%m%d_%H%M%S"));
+        let path = dir.join(name);
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&path, json)
+            .await
+            .with_context(|| format!("Failed to write run report: {}", path.display()))?;
+        Ok(path)
+    }
+}
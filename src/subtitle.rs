@@ -1,97 +1,336 @@
+use crate::config::{Config, OpenSubtitlesConfig, SubtitleProviderKind};
+use crate::ffmpeg::{self, SubtitleStreamInfo};
+use crate::{logi, logok, logw};
 use anyhow::{Context, Result};
+use async_trait::async_trait;
 use once_cell::sync::OnceCell;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 use tokio::fs;
-use tokio::io::AsyncWriteExt;
-use tracing::warn;
+use zip::ZipArchive;
 
 const SUBF2M_BASE: &str = "https://subf2m.co";
 const USER_AGENT: &str =
     "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.0 Safari/605.1.15";
 
-pub struct SubtitleDownloader {
-    pub client: reqwest::Client,
+/// What the chain knows about the movie whose subtitles it is looking for.
+#[derive(Debug, Clone, Copy)]
+pub struct SubtitleQuery<'a> {
+    pub movie_title: &'a str,
+    pub movie_path: &'a Path,
 }
 
-impl SubtitleDownloader {
-    pub fn new() -> Result<Self> {
-        let client = reqwest::Client::builder()
-            .cookie_store(true)
-            .user_agent(USER_AGENT)
-            .timeout(Duration::from_secs(120))
-            .connect_timeout(Duration::from_secs(30))
-            .build()
-            .context("failed to build reqwest client")?;
-        Ok(Self { client })
+/// Result of one provider attempt. `Missing` carries the reason so it can be logged and recorded.
+#[derive(Debug, Clone)]
+pub enum FetchOutcome {
+    Found,
+    Missing(String),
+}
+
+/// One source of English SRT subtitles, tried in the order configured in `config.json`.
+#[async_trait]
+pub trait SubtitleProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn fetch(&self, query: &SubtitleQuery<'_>, dest_srt_path: &Path) -> Result<FetchOutcome>;
+}
+
+/// One line of the subtitle acquisition log, kept in the run report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubtitleAttempt {
+    pub provider: String,
+    pub ok: bool,
+    #[serde(default)]
+    pub reason: String,
+}
+
+pub struct SubtitleChain {
+    providers: Vec<Box<dyn SubtitleProvider>>,
+}
+
+impl SubtitleChain {
+    pub fn from_config(cfg: &Config, client: &reqwest::Client) -> Self {
+        let sc = &cfg.subtitles;
+        let providers = sc
+            .providers
+            .iter()
+            .map(|kind| -> Box<dyn SubtitleProvider> {
+                match kind {
+                    SubtitleProviderKind::Local => Box::new(LocalFolderProvider {
+                        dirs: sc.local_dirs.iter().map(PathBuf::from).collect(),
+                    }),
+                    SubtitleProviderKind::Embedded => Box::new(EmbeddedTrackProvider),
+                    SubtitleProviderKind::Subf2m => Box::new(Subf2mProvider {
+                        client: client.clone(),
+                    }),
+                    SubtitleProviderKind::Opensubtitles => Box::new(OpenSubtitlesProvider {
+                        client: client.clone(),
+                        cfg: sc.opensubtitles.clone(),
+                    }),
+                }
+            })
+            .collect();
+        Self { providers }
     }
 
-    pub fn with_client(client: reqwest::Client) -> Self {
-        Self { client }
+    pub fn describe(&self) -> String {
+        self.providers.iter().map(|p| p.name()).collect::<Vec<_>>().join(" -> ")
     }
 
-    pub async fn download_subtitle_srt(
-        &self,
-        movie_title: &str,
-        dest_srt_path: PathBuf,
-    ) -> Result<bool> {
-        fs::create_dir_all("scripts").await.ok();
-        fs::create_dir_all("scripts/srt_files").await.ok();
+    /// Tries each provider in turn until one writes `dest_srt_path`. Provider errors are
+    /// treated as failed attempts so the chain can fall through to the next source.
+    pub async fn fetch(&self, query: &SubtitleQuery<'_>, dest_srt_path: &Path) -> Result<(bool, Vec<SubtitleAttempt>)> {
         if let Some(parent) = dest_srt_path.parent() {
             fs::create_dir_all(parent).await.ok();
         }
 
-        let slug = parse_movie_title_slug(movie_title);
-        let list_url = format!("{SUBF2M_BASE}/subtitles/{}/english", slug);
+        let mut attempts = Vec::new();
+        for provider in &self.providers {
+            logi(format!("Subtitles: trying {} for {}...", provider.name(), query.movie_title));
+            let outcome = match provider.fetch(query, dest_srt_path).await {
+                Ok(outcome) => outcome,
+                Err(err) => FetchOutcome::Missing(format!("error: {:#}", err)),
+            };
+
+            match outcome {
+                FetchOutcome::Found if fs::metadata(dest_srt_path).await.is_ok() => {
+                    logok(format!("Subtitles: {} succeeded for {}", provider.name(), query.movie_title));
+                    attempts.push(SubtitleAttempt {
+                        provider: provider.name().to_string(),
+                        ok: true,
+                        reason: String::new(),
+                    });
+                    return Ok((true, attempts));
+                }
+                FetchOutcome::Found => {
+                    let reason = "reported success but wrote no file".to_string();
+                    logw(format!("Subtitles: {} failed: {}", provider.name(), reason));
+                    attempts.push(SubtitleAttempt {
+                        provider: provider.name().to_string(),
+                        ok: false,
+                        reason,
+                    });
+                }
+                FetchOutcome::Missing(reason) => {
+                    logw(format!("Subtitles: {} failed: {}", provider.name(), reason));
+                    attempts.push(SubtitleAttempt {
+                        provider: provider.name().to_string(),
+                        ok: false,
+                        reason,
+                    });
+                }
+            }
+        }
+
+        Ok((false, attempts))
+    }
+}
+
+/// Looks for `{title}.srt` (case-insensitively) in the configured folders.
+pub struct LocalFolderProvider {
+    dirs: Vec<PathBuf>,
+}
+
+#[async_trait]
+impl SubtitleProvider for LocalFolderProvider {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    async fn fetch(&self, query: &SubtitleQuery<'_>, dest_srt_path: &Path) -> Result<FetchOutcome> {
+        let want = format!("{}.srt", query.movie_title).to_lowercase();
+        for dir in &self.dirs {
+            let Ok(mut entries) = fs::read_dir(dir).await else {
+                continue;
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let name = entry.file_name().to_string_lossy().to_lowercase();
+                if name != want {
+                    continue;
+                }
+                let path = entry.path();
+                if path == dest_srt_path {
+                    continue;
+                }
+                fs::copy(&path, dest_srt_path)
+                    .await
+                    .with_context(|| format!("copy {} -> {}", path.display(), dest_srt_path.display()))?;
+                logi(format!("Subtitles: copied local file {}", path.display()));
+                return Ok(FetchOutcome::Found);
+            }
+        }
+
+        let searched = self
+            .dirs
+            .iter()
+            .map(|d| d.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        Ok(FetchOutcome::Missing(format!("no {}.srt in [{}]", query.movie_title, searched)))
+    }
+}
+
+/// Extracts the best English text subtitle stream already muxed into the movie file.
+/// Bitmap tracks (PGS/VobSub/DVB) are reported as unsupported since they would need OCR.
+pub struct EmbeddedTrackProvider;
+
+fn is_english(stream: &SubtitleStreamInfo) -> bool {
+    let lang = stream.language.to_ascii_lowercase();
+    matches!(lang.as_str(), "eng" | "en" | "en-us" | "en-gb")
+        || ((lang.is_empty() || lang == "und") && stream.title.to_ascii_lowercase().contains("english"))
+}
 
-        let list_page = match self.fetch_text(&list_url).await? {
-            Some(body) => body,
-            None => {
-                warn!("subf2m list HTTP failure for {list_url}");
-                return Ok(false);
+fn is_forced(stream: &SubtitleStreamInfo) -> bool {
+    stream.forced || stream.title.to_ascii_lowercase().contains("forced")
+}
+
+fn is_sdh(stream: &SubtitleStreamInfo) -> bool {
+    let title = stream.title.to_ascii_lowercase();
+    stream.hearing_impaired || title.contains("sdh") || title.contains("hearing") || title.split_whitespace().any(|w| w == "cc")
+}
+
+/// Lower is better: full dialogue first, SDH next (extra sound cues), forced last (only foreign lines).
+fn embedded_rank(stream: &SubtitleStreamInfo) -> (bool, bool, i64) {
+    (is_forced(stream), is_sdh(stream), stream.index)
+}
+
+fn describe_stream(stream: &SubtitleStreamInfo) -> String {
+    let mut label = format!("#{} {}", stream.index, stream.codec);
+    if !stream.language.is_empty() {
+        label.push_str(&format!(" [{}]", stream.language));
+    }
+    if !stream.title.is_empty() {
+        label.push_str(&format!(" \"{}\"", stream.title));
+    }
+    label
+}
+
+#[async_trait]
+impl SubtitleProvider for EmbeddedTrackProvider {
+    fn name(&self) -> &'static str {
+        "embedded"
+    }
+
+    async fn fetch(&self, query: &SubtitleQuery<'_>, dest_srt_path: &Path) -> Result<FetchOutcome> {
+        let streams = ffmpeg::ffprobe_subtitle_streams(query.movie_path).await?;
+        if streams.is_empty() {
+            return Ok(FetchOutcome::Missing("movie has no subtitle streams".to_string()));
+        }
+
+        let english: Vec<&SubtitleStreamInfo> = streams.iter().filter(|s| is_english(s)).collect();
+        for stream in english.iter().filter(|s| s.is_bitmap()) {
+            logw(format!(
+                "Subtitles: embedded track {} is bitmap-based; OCR is unsupported, skipping.",
+                describe_stream(stream)
+            ));
+        }
+
+        let mut text_tracks: Vec<&SubtitleStreamInfo> =
+            english.iter().copied().filter(|s| !s.is_bitmap()).collect();
+        text_tracks.sort_by_key(|s| embedded_rank(s));
+
+        for stream in text_tracks {
+            logi(format!("Subtitles: extracting embedded track {}", describe_stream(stream)));
+            let spec = format!("0:{}", stream.index);
+            if ffmpeg::ffmpeg_extract_subtitle(query.movie_path, &spec, dest_srt_path).await? {
+                return Ok(FetchOutcome::Found);
             }
+        }
+
+        let reason = if english.iter().any(|s| s.is_bitmap()) {
+            let bitmap = english
+                .iter()
+                .filter(|s| s.is_bitmap())
+                .map(|s| describe_stream(s))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("only bitmap English tracks ({}); OCR is unsupported", bitmap)
+        } else if english.is_empty() {
+            let all = streams.iter().map(describe_stream).collect::<Vec<_>>().join(", ");
+            format!("no English subtitle stream (found: {})", all)
+        } else {
+            "English text tracks failed to convert to SRT".to_string()
         };
+        Ok(FetchOutcome::Missing(reason))
+    }
+}
 
-        let want_subpage_prefix = format!("/subtitles/{}/english/", slug);
-        let mut subpage_url = None;
+/// Scrapes subf2m.co for the first English subtitle of the movie.
+pub struct Subf2mProvider {
+    client: reqwest::Client,
+}
 
-        for href in extract_hrefs(&list_page)? {
-            if href.starts_with(&want_subpage_prefix) {
+impl Subf2mProvider {
+    async fn fetch_text(&self, url: &str) -> Result<(reqwest::StatusCode, String)> {
+        let resp = self
+            .client
+            .get(url)
+            .header("User-Agent", USER_AGENT)
+            .header("Accept-Encoding", "")
+            .timeout(Duration::from_secs(30))
+            .send()
+            .await?;
+
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+        Ok((status, text))
+    }
+}
+
+#[async_trait]
+impl SubtitleProvider for Subf2mProvider {
+    fn name(&self) -> &'static str {
+        "subf2m"
+    }
+
+    async fn fetch(&self, query: &SubtitleQuery<'_>, dest_srt_path: &Path) -> Result<FetchOutcome> {
+        let movie_title = query.movie_title;
+        let slug = parse_movie_title_slug(movie_title);
+        let list_url = format!("{SUBF2M_BASE}/subtitles/{}/english", slug);
+        let (code, page) = self.fetch_text(&list_url).await?;
+        if !code.is_success() || page.is_empty() {
+            return Ok(FetchOutcome::Missing(format!("list HTTP {} for {}", code.as_u16(), list_url)));
+        }
+
+        let want_prefix = format!("/subtitles/{}/english/", slug);
+        let mut subpage_url = String::new();
+
+        let href_re = href_regex()?;
+        for cap in href_re.captures_iter(&page) {
+            let href = &cap[1];
+            if href.starts_with(&want_prefix) {
                 if href.contains("english-german") {
                     continue;
                 }
-                subpage_url = Some(format!("{SUBF2M_BASE}{href}"));
+                subpage_url = format!("{SUBF2M_BASE}{}", href);
                 break;
             }
         }
 
-        if subpage_url.is_none() {
+        if subpage_url.is_empty() {
             let mut tried_profiles = 0;
-            for href in extract_hrefs(&list_page)? {
+            for cap in href_re.captures_iter(&page) {
+                let href = &cap[1];
                 if !href.starts_with("/u/") {
                     continue;
                 }
-
-                let profile_url = format!("{SUBF2M_BASE}{href}");
-                let profile_page = match self.fetch_text(&profile_url).await? {
-                    Some(body) => body,
-                    None => {
-                        continue;
-                    }
-                };
-
-                for phref in extract_hrefs(&profile_page)? {
-                    if phref.starts_with(&want_subpage_prefix) {
-                        subpage_url = Some(format!("{SUBF2M_BASE}{phref}"));
+                let profile_url = format!("{SUBF2M_BASE}{}", href);
+                let (pcode, prof) = self.fetch_text(&profile_url).await?;
+                if !pcode.is_success() || prof.is_empty() {
+                    continue;
+                }
+                for cap2 in href_re.captures_iter(&prof) {
+                    let phref = &cap2[1];
+                    if phref.starts_with(&want_prefix) {
+                        subpage_url = format!("{SUBF2M_BASE}{}", phref);
                         break;
                     }
                 }
-
-                if subpage_url.is_some() {
+                if !subpage_url.is_empty() {
                     break;
                 }
-
                 tried_profiles += 1;
                 if tried_profiles >= 12 {
                     break;
@@ -99,138 +338,198 @@ impl SubtitleDownloader {
             }
         }
 
-        let Some(subpage_url) = subpage_url else {
-            warn!("subf2m: couldn't locate subtitle detail page for {movie_title} (slug={slug})");
-            return Ok(false);
-        };
+        if subpage_url.is_empty() {
+            return Ok(FetchOutcome::Missing(format!("couldn't locate subtitle detail page (slug={})", slug)));
+        }
 
-        let subpage = match self.fetch_text(&subpage_url).await? {
-            Some(body) => body,
-            None => {
-                warn!("subf2m: subtitle detail HTTP failure for {subpage_url}");
-                return Ok(false);
-            }
-        };
+        let (scode, subpage) = self.fetch_text(&subpage_url).await?;
+        if !scode.is_success() || subpage.is_empty() {
+            return Ok(FetchOutcome::Missing(format!("detail HTTP {} for {}", scode.as_u16(), subpage_url)));
+        }
 
-        let mut download_url = None;
-        for href in extract_hrefs(&subpage)? {
-            if str_ends_with(&href, "download") {
-                download_url = Some(format!("{SUBF2M_BASE}{href}"));
+        let mut download_url = String::new();
+        for cap in href_re.captures_iter(&subpage) {
+            let href = &cap[1];
+            if href.ends_with("download") {
+                download_url = format!("{SUBF2M_BASE}{}", href);
                 break;
             }
         }
 
-        let Some(download_url) = download_url else {
-            warn!("subf2m: couldn't find download link on {subpage_url}");
-            return Ok(false);
-        };
+        if download_url.is_empty() {
+            return Ok(FetchOutcome::Missing(format!("no download link on {}", subpage_url)));
+        }
+
+        let zip_bytes = self
+            .client
+            .get(&download_url)
+            .header("User-Agent", USER_AGENT)
+            .header("Cookie", "")
+            .timeout(Duration::from_secs(120))
+            .send()
+            .await?
+            .bytes()
+            .await?;
 
-        let tmpzip_path = PathBuf::from("scripts/srt_files").join(format!("{movie_title}_tmp.zip"));
-        let download_resp = self.client.get(&download_url).send().await?;
-        if !download_resp.status().is_success() {
-            warn!("subf2m: zip download HTTP {} for {}", download_resp.status(), download_url);
-            return Ok(false);
+        if extract_srt_from_zip(zip_bytes.to_vec(), dest_srt_path).await? {
+            Ok(FetchOutcome::Found)
+        } else {
+            Ok(FetchOutcome::Missing("downloaded archive contains no .srt".to_string()))
         }
+    }
+}
 
-        let zip_bytes = download_resp.bytes().await?;
-        let mut tmp_file = fs::File::create(&tmpzip_path)
-            .await
-            .with_context(|| format!("create temp zip: {}", tmpzip_path.display()))?;
-        tmp_file.write_all(&zip_bytes).await?;
-        tmp_file.flush().await.ok();
+/// OpenSubtitles REST API (`/subtitles` search + `/download`), or any local stand-in
+/// that speaks the same JSON.
+pub struct OpenSubtitlesProvider {
+    client: reqwest::Client,
+    cfg: OpenSubtitlesConfig,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsSearchResponse {
+    #[serde(default)]
+    data: Vec<OsSearchItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsSearchItem {
+    attributes: OsAttributes,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsAttributes {
+    #[serde(default)]
+    download_count: i64,
+    #[serde(default)]
+    hearing_impaired: bool,
+    #[serde(default)]
+    files: Vec<OsFile>,
+}
 
-        let extracted = extract_srt_from_zip(&tmpzip_path, &dest_srt_path).await?;
-        let _ = fs::remove_file(&tmpzip_path).await;
+#[derive(Debug, Deserialize)]
+struct OsFile {
+    file_id: i64,
+}
 
-        if !extracted {
-            return Ok(false);
+#[derive(Debug, Deserialize)]
+struct OsDownloadResponse {
+    link: String,
+}
+
+impl OpenSubtitlesProvider {
+    fn request(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        let builder = builder
+            .header("User-Agent", &self.cfg.user_agent)
+            .header("Accept", "application/json")
+            .timeout(Duration::from_secs(60));
+        if self.cfg.api_key.is_empty() {
+            builder
+        } else {
+            builder.header("Api-Key", &self.cfg.api_key)
         }
+    }
+}
 
-        Ok(fs::metadata(&dest_srt_path).await.is_ok())
+#[async_trait]
+impl SubtitleProvider for OpenSubtitlesProvider {
+    fn name(&self) -> &'static str {
+        "opensubtitles"
     }
 
-    async fn fetch_text(&self, url: &str) -> Result<Option<String>> {
-        let resp = self.client.get(url).send().await?;
-        let status = resp.status();
-        if !status.is_success() {
-            return Ok(None);
+    async fn fetch(&self, query: &SubtitleQuery<'_>, dest_srt_path: &Path) -> Result<FetchOutcome> {
+        let base = self.cfg.base_url.trim_end_matches('/');
+        if base.is_empty() {
+            return Ok(FetchOutcome::Missing("no base_url configured".to_string()));
+        }
+
+        let search_url = format!("{}/subtitles", base);
+        let resp = self
+            .request(self.client.get(&search_url))
+            .query(&[("query", query.movie_title), ("languages", "en")])
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            return Ok(FetchOutcome::Missing(format!("search HTTP {}", resp.status().as_u16())));
+        }
+        let search: OsSearchResponse = resp.json().await.context("parse OpenSubtitles search response")?;
+
+        // Most-downloaded non-SDH release first; SDH only as a fallback.
+        let best = search
+            .data
+            .iter()
+            .filter(|item| !item.attributes.files.is_empty())
+            .max_by_key(|item| (!item.attributes.hearing_impaired, item.attributes.download_count));
+        let Some(best) = best else {
+            return Ok(FetchOutcome::Missing(format!("no results for \"{}\"", query.movie_title)));
+        };
+        let file_id = best.attributes.files[0].file_id;
+
+        let download_url = format!("{}/download", base);
+        let resp = self
+            .request(self.client.post(&download_url))
+            .json(&serde_json::json!({ "file_id": file_id }))
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            return Ok(FetchOutcome::Missing(format!("download HTTP {} (file_id={})", resp.status().as_u16(), file_id)));
+        }
+        let link: OsDownloadResponse = resp.json().await.context("parse OpenSubtitles download response")?;
+
+        let resp = self.client.get(&link.link).timeout(Duration::from_secs(120)).send().await?;
+        if !resp.status().is_success() {
+            return Ok(FetchOutcome::Missing(format!("file HTTP {} for {}", resp.status().as_u16(), link.link)));
         }
-        let text = resp.text().await?;
-        if text.is_empty() {
-            return Ok(None);
+        let bytes = resp.bytes().await?;
+        if bytes.is_empty() {
+            return Ok(FetchOutcome::Missing("empty subtitle file".to_string()));
         }
-        Ok(Some(text))
+        fs::write(dest_srt_path, &bytes).await?;
+        Ok(FetchOutcome::Found)
     }
 }
 
 fn parse_movie_title_slug(movie_title: &str) -> String {
     let mut out = String::new();
     for ch in movie_title.chars() {
-        if ch == '\'' || ch == '(' || ch == ')' {
-            continue;
-        }
-        if ch == ' ' {
-            out.push('-');
-        } else {
-            out.push(ch.to_ascii_lowercase());
+        match ch {
+            '\'' | '(' | ')' => continue,
+            ' ' => out.push('-'),
+            _ => out.push(ch.to_ascii_lowercase()),
         }
     }
 
-    let base = out.clone();
-    let n = base.len();
-    if n >= 2 && base.ends_with("ii") {
+    if out.ends_with("ii") {
         out.push_str("-2");
     }
-    if n >= 3 && base.ends_with("iii") {
+    if out.ends_with("iii") {
         out.push_str("-3");
     }
-    if n >= 2 && base.ends_with("iv") {
+    if out.ends_with("iv") {
         out.push_str("-4");
     }
-
     out
 }
 
-fn str_ends_with(s: &str, suffix: &str) -> bool {
-    s.ends_with(suffix)
-}
-
-fn extract_hrefs(html: &str) -> Result<Vec<String>> {
-    let re = href_regex()?;
-    let mut out = Vec::new();
-    for cap in re.captures_iter(html) {
-        if let Some(m) = cap.get(2) {
-            out.push(m.as_str().to_string());
-        }
-    }
-    Ok(out)
-}
-
 fn href_regex() -> Result<&'static Regex> {
     static HREF_RE: OnceCell<Regex> = OnceCell::new();
     HREF_RE.get_or_try_init(|| {
-        Regex::new(r#"(?i)href\s*=\s*(['\"])(.*?)\1"#)
-            .context("failed to compile href regex")
+        Regex::new(r#"href=["']([^"']+)["']"#).context("failed to compile href regex")
     })
 }
 
-async fn extract_srt_from_zip(tmpzip_path: &Path, dest_srt_path: &Path) -> Result<bool> {
-    let tmpzip_path = tmpzip_path.to_owned();
+async fn extract_srt_from_zip(zip_bytes: Vec<u8>, dest_srt_path: &Path) -> Result<bool> {
     let dest_srt_path = dest_srt_path.to_owned();
 
     tokio::task::spawn_blocking(move || -> Result<bool> {
-        let file = std::fs::File::open(&tmpzip_path)
-            .with_context(|| format!("open zip: {}", tmpzip_path.display()))?;
-        let mut archive = zip::ZipArchive::new(file).context("read zip archive")?;
+        let mut archive = ZipArchive::new(std::io::Cursor::new(zip_bytes)).context("read zip archive")?;
 
         for i in 0..archive.len() {
             let mut entry = archive.by_index(i).context("read zip entry")?;
             if entry.is_dir() {
                 continue;
             }
-
-            let name = entry.name().to_string();
-            if !name.to_ascii_lowercase().ends_with(".srt") {
+            if !entry.name().to_ascii_lowercase().ends_with(".srt") {
                 continue;
             }
 